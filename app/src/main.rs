@@ -1,6 +1,6 @@
 use log::{info, error, LevelFilter};
 
-use astm::{ASTM, ASTMError};
+use astm::ASTMError;
 use instruments::{Instruments, InstError};
 
 mod error;
@@ -56,7 +56,7 @@ async fn main() {
 }
 
 async fn wrapper() -> Result<()> {
-    let inst = Instruments::new().await?;
+    let _inst = Instruments::new().await?;
 
     Ok(())
 }
@@ -0,0 +1,101 @@
+use encoding::label::encoding_from_whatwg_label;
+use encoding::types::EncodingRef;
+use encoding::{all, DecoderTrap, EncoderTrap};
+use std::fmt;
+
+use crate::{ASTMError, Result};
+
+/// Character encoding of the data content, from the full table of the
+/// `encoding` crate.
+#[derive(Clone)]
+pub struct CharEncoding {
+    encoding: EncodingRef,
+    // Unmappable characters are replaced instead of rejected.
+    replacement: bool,
+}
+
+impl CharEncoding {
+    pub const ASCII: Self = Self::new(all::ASCII);
+    pub const UTF8: Self = Self::new(all::UTF_8);
+    pub const ISO_8859_1: Self = Self::new(all::ISO_8859_1);
+    pub const WINDOWS_1251: Self = Self::new(all::WINDOWS_1251);
+    pub const WINDOWS_1252: Self = Self::new(all::WINDOWS_1252);
+    pub const SHIFT_JIS: Self = Self::new(all::WINDOWS_31J);
+    pub const GB18030: Self = Self::new(all::GB18030);
+
+    const fn new(encoding: EncodingRef) -> Self {
+        Self {
+            encoding,
+            replacement: false,
+        }
+    }
+
+    /// Looks up an encoding by name, such as `iso-8859-1` or `gb18030`,
+    /// or else by its WHATWG label, such as `shift_jis` or `latin1`.
+    /// Names are case insensitive.
+    pub fn from_label(src: &str) -> Result<Self> {
+        let label = src.trim().to_lowercase();
+
+        all::encodings()
+            .iter()
+            .find(|t| t.name() == label && t.name() != "error")
+            .copied()
+            .or_else(|| encoding_from_whatwg_label(&label))
+            .map(Self::new)
+            .ok_or(ASTMError::UnknownEncoding(src.to_string()))
+    }
+
+    /// Replaces unmappable characters, with `?` when encoding and
+    /// U+FFFD when decoding, instead of failing.
+    pub fn with_replacement(mut self) -> Self {
+        self.replacement = true;
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub(crate) fn encode(&self, src: &str) -> Result<Vec<u8>> {
+        let trap = match self.replacement {
+            true => EncoderTrap::Replace,
+            false => EncoderTrap::Strict,
+        };
+
+        self.encoding
+            .encode(src, trap)
+            .map_err(|t| ASTMError::Charset(self.name(), t))
+    }
+
+    pub(crate) fn decode(&self, src: &[u8]) -> Result<String> {
+        let trap = match self.replacement {
+            true => DecoderTrap::Replace,
+            false => DecoderTrap::Strict,
+        };
+
+        self.encoding
+            .decode(src, trap)
+            .map_err(|t| ASTMError::Charset(self.name(), t))
+    }
+}
+
+impl Default for CharEncoding {
+    fn default() -> Self {
+        Self::ASCII
+    }
+}
+
+impl fmt::Debug for CharEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CharEncoding")
+            .field("name", &self.name())
+            .field("replacement", &self.replacement)
+            .finish()
+    }
+}
+
+impl PartialEq for CharEncoding {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name() && self.replacement == other.replacement
+    }
+}
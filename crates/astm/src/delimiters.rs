@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use crate::{ASTMError, Result};
+
+/// Field, repeat, component and escape delimiters of a message, declared
+/// by the header right after the `H`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delimiters {
+    pub field: char,
+    pub repeat: char,
+    pub component: char,
+    pub escape: char,
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self {
+            field: '|',
+            repeat: '\\',
+            component: '^',
+            escape: '&',
+        }
+    }
+}
+
+impl Delimiters {
+    /// Reads the delimiters declared by a header record, such as
+    /// `H|\^&|||`: the field delimiter follows the `H`, then come the
+    /// repeat, component and escape delimiters.
+    pub fn from_header(src: &str) -> Result<Self> {
+        let definition: String = src.chars().take(6).collect();
+        let mut chars = definition.chars();
+
+        if chars.next() != Some('H') {
+            return Err(ASTMError::MissingHeaderRecord);
+        }
+
+        let dst = match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (Some(field), Some(repeat), Some(component), Some(escape)) => Self {
+                field,
+                repeat,
+                component,
+                escape,
+            },
+            _ => return Err(ASTMError::InvalidDelimiters(definition)),
+        };
+
+        let delimiters = [dst.field, dst.repeat, dst.component, dst.escape];
+        let valid = delimiters.iter().enumerate().all(|(index, t)| {
+            !t.is_alphanumeric() && !t.is_whitespace() && !delimiters[..index].contains(t)
+        });
+
+        match chars.next() {
+            Some(t) if t != dst.field && t != '\r' => Err(ASTMError::InvalidDelimiters(definition)),
+            _ if !valid => Err(ASTMError::InvalidDelimiters(definition)),
+            _ => Ok(dst),
+        }
+    }
+
+    /// Repeat, component and escape delimiters, as written in the header
+    /// after the field delimiter.
+    pub fn definition(&self) -> String {
+        format!("{}{}{}", self.repeat, self.component, self.escape)
+    }
+
+    /// Replaces the delimiters in a value with `&F&`, `&R&`, `&S&` and
+    /// `&E&`, and control characters with `&Xhh&`.
+    pub fn escape(&self, src: &str) -> String {
+        let mut dst = String::with_capacity(src.len());
+
+        for t in src.chars() {
+            let code = match t {
+                t if t == self.field => "F".to_string(),
+                t if t == self.repeat => "R".to_string(),
+                t if t == self.component => "S".to_string(),
+                t if t == self.escape => "E".to_string(),
+                t if t.is_ascii_control() => format!("X{:02X}", t as u8),
+                t => {
+                    dst.push(t);
+                    continue;
+                }
+            };
+            let _ = write!(dst, "{}{}{}", self.escape, code, self.escape);
+        }
+
+        dst
+    }
+
+    /// Replaces escape sequences with the text they stand for. Sequences
+    /// that are not known, or an escape delimiter sent without escaping,
+    /// are kept as they are.
+    pub fn unescape(&self, src: &str) -> String {
+        let mut dst = String::with_capacity(src.len());
+        let mut rest = src;
+
+        while let Some(start) = rest.find(self.escape) {
+            dst.push_str(&rest[..start]);
+            let tail = &rest[start + self.escape.len_utf8()..];
+
+            let sequence = tail
+                .find(self.escape)
+                .and_then(|end| Some((end, self.sequence(&tail[..end])?)));
+
+            match sequence {
+                Some((end, t)) => {
+                    dst.push_str(&t);
+                    rest = &tail[end + self.escape.len_utf8()..];
+                }
+                None => {
+                    dst.push(self.escape);
+                    rest = tail;
+                }
+            }
+        }
+
+        dst.push_str(rest);
+        dst
+    }
+
+    fn sequence(&self, src: &str) -> Option<String> {
+        match src {
+            "F" => Some(self.field.to_string()),
+            "R" => Some(self.repeat.to_string()),
+            "S" => Some(self.component.to_string()),
+            "E" => Some(self.escape.to_string()),
+            _ => hex(src.strip_prefix('X')?),
+        }
+    }
+}
+
+// Pairs of hex digits, each one a byte. Bytes that are not UTF-8 are
+// taken as Latin-1.
+fn hex(src: &str) -> Option<String> {
+    if src.is_empty() {
+        return None;
+    }
+
+    let digit = |t: &u8| char::from(*t).to_digit(16);
+    let bytes = src
+        .as_bytes()
+        .chunks(2)
+        .map(|t| match t {
+            [high, low] => Some((digit(high)? * 16 + digit(low)?) as u8),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()?;
+
+    Some(
+        String::from_utf8(bytes)
+            .unwrap_or_else(|t| t.into_bytes().into_iter().map(char::from).collect()),
+    )
+}
@@ -0,0 +1,113 @@
+use log::debug;
+use std::collections::VecDeque;
+use std::future::pending;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::{interval_at, sleep_until, Duration, Instant};
+
+use crate::link::{DataLink, Input, Output, TokioClock};
+use crate::trace::{Capture, Direction};
+use crate::{ASTMError, Action, Result, ASTM};
+
+/// Runs the data link over a tokio reader and writer until the reader
+/// is closed.
+pub(crate) async fn drive<S, R, W>(mut read: R, mut write: W, astm: ASTM<S>) -> Result<()>
+where
+    S: Clone + Sync + Action<S>,
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let clock = Arc::new(TokioClock::default());
+    let mut data_link = DataLink::new(astm.settings.clone(), clock.clone());
+    let capture = astm.trace.as_ref().map(|t| t.connection());
+
+    let mut interval = astm.interval.map(|t| {
+        let period = Duration::from_millis(t);
+        interval_at(Instant::now() + period, period)
+    });
+
+    let mut buffer = [0_u8; 4096];
+
+    loop {
+        let timer = data_link.next_timer();
+
+        let input = tokio::select! {
+            size = read.read(&mut buffer) => match size {
+                Ok(0) => return Ok(()),
+                Ok(size) => {
+                    if let Some(capture) = &capture {
+                        capture.record(Direction::In, &buffer[0..size]).await;
+                    }
+                    Input::Bytes(buffer[0..size].to_vec())
+                }
+                Err(err) => return Err(ASTMError::Connection(err.to_string())),
+            },
+            _ = async {
+                match timer {
+                    Some((_, t)) => sleep_until(clock.instant(t)).await,
+                    None => pending().await,
+                }
+            } => match timer {
+                Some((t, _)) => Input::Timer(t),
+                None => continue,
+            },
+            _ = async {
+                match interval.as_mut() {
+                    Some(t) => t.tick().await,
+                    None => pending().await,
+                }
+            } => match astm.instrument.on_idle_interval().await {
+                Some(t) => Input::Queue(t),
+                None => continue,
+            },
+        };
+
+        process(&mut data_link, input, &astm, &capture, &mut write).await?;
+    }
+}
+
+pub(crate) async fn process<S, W>(
+    data_link: &mut DataLink,
+    input: Input,
+    astm: &ASTM<S>,
+    capture: &Option<Capture>,
+    write: &mut W,
+) -> Result<()>
+where
+    S: Clone + Sync + Action<S>,
+    W: AsyncWrite + Unpin,
+{
+    let mut inputs = VecDeque::from([input]);
+
+    while let Some(input) = inputs.pop_front() {
+        for output in data_link.handle(input) {
+            match output {
+                Output::Send(t) => {
+                    if let Some(capture) = capture {
+                        capture.record(Direction::Out, &t).await;
+                    }
+                    write
+                        .write_all(&t)
+                        .await
+                        .map_err(|t| ASTMError::Connection(t.to_string()))?
+                }
+                Output::CheckFrame(frame, message) => {
+                    let checked = astm.instrument.on_recv_frame(frame, &message).await;
+                    inputs.push_back(Input::FrameChecked(checked));
+                }
+                Output::Deliver(message) => {
+                    if let Some(t) = astm.instrument.on_recv_message(&message).await {
+                        inputs.push_back(Input::Queue(t));
+                    }
+                }
+                Output::Abort(message) => astm.instrument.on_send_abort(&message).await,
+                Output::State(state) => astm.instrument.on_state_change(&state).await,
+                Output::Queued(queued) => astm.instrument.on_queue_change(queued).await,
+                Output::StartTimer(timer, at) => debug!("Timer {:?} set to {} us.", timer, at),
+                Output::StopTimer(timer) => debug!("Timer {:?} stopped.", timer),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,19 +1,14 @@
 use thiserror::Error;
 use std::borrow::Cow;
+use std::fmt;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ASTMError {
     // data
-    #[error("Error decoding ASCII frame. {0}")]
-    DecodingASCIIFrame(Cow<'static, str>),
-    #[error("Error encoding ASCII frame. {0}")]
-    EncodingASCIIFrame(Cow<'static, str>),
-    #[error("Error decoding WINDOWS-1251 frame. {0}")]
-    DecodingWINDOWS1251Frame(Cow<'static, str>),
-    #[error("Error encoding WINDOWS-1251 frame. {0}")]
-    EncodingWINDOWS1251Frame(Cow<'static, str>),
-    #[error("Error decoding UTF8 frame. {0}")]
-    DecodingUTF8Frame(std::str::Utf8Error),
+    #[error("Error converting {0} text. {1}")]
+    Charset(&'static str, Cow<'static, str>),
+    #[error("Unknown character encoding {0}.")]
+    UnknownEncoding(String),
     #[error("Invalid frame start character <STX>.")]
     InvalidSTXCharacter,
     #[error("Missing frame start character <STX>.")]
@@ -44,8 +39,28 @@ pub enum ASTMError {
     OversizedMessage,
     #[error("Defective frame. Checksum must be: {0}.")]
     DefectiveFrame(String),
+    #[error("Duplicate frame {0}. Frame was already received.")]
+    DuplicateFrame(u8),
+    #[error("Out of sequence frame. Expected frame number {0}, received {1}.")]
+    OutOfSequenceFrame(u8, u8),
 
     // records
+    #[error("Missing header record.")]
+    MissingHeaderRecord,
+    #[error("Invalid delimiter definition {0}.")]
+    InvalidDelimiters(String),
+    #[error("Invalid record type {0}.")]
+    InvalidRecordType(String),
+    #[error("Unknown component {0}.")]
+    UnknownComponent(String),
+    #[error("Invalid component {component} {text:?}. {error}")]
+    InvalidComponent {
+        component: usize,
+        text: String,
+        error: Box<ASTMError>,
+    },
+    #[error("{0}")]
+    InvalidField(Box<FieldError>),
     #[error("Invalid Processing ID value.")]
     InvalidProcessingIDValue,
     #[error("Invalid Patient Sex value.")]
@@ -54,6 +69,22 @@ pub enum ASTMError {
     MissingMeasurementValue,
     #[error("Invalid Marital Status value.")]
     InvalidMaritalStatusValue,
+    #[error("Invalid Order Priority value.")]
+    InvalidOrderPriorityValue,
+    #[error("Invalid Action Code value.")]
+    InvalidActionCodeValue,
+    #[error("Invalid Report Type value.")]
+    InvalidReportTypeValue,
+    #[error("Invalid Result Status value.")]
+    InvalidResultStatusValue,
+    #[error("Invalid Comment Source value.")]
+    InvalidCommentSourceValue,
+    #[error("Invalid Comment Type value.")]
+    InvalidCommentTypeValue,
+    #[error("Invalid Request Status value.")]
+    InvalidRequestStatusValue,
+    #[error("Invalid Termination Code value.")]
+    InvalidTerminationCodeValue,
     #[error("Invalid Date and Time value. {0}")]
     InvalidDateAndTimeValue(chrono::format::ParseError),
     #[error("Invalid Date value. {0}")]
@@ -70,4 +101,68 @@ pub enum ASTMError {
     TcpBind(String),
     #[error("Error accepting incoming connection. {0}")]
     TcpAccept(String),
+    #[error("Connection error. {0}")]
+    Connection(String),
+    #[error("Error writing trace file. {0}")]
+    Trace(String),
+
+    // replay
+    #[error("Error reading recording. {0}")]
+    ReadRecording(String),
+    #[error("{0}")]
+    InvalidTraceEntry(String),
+    #[error("Invalid trace line {0}. {1}")]
+    InvalidTraceLine(usize, String),
+    #[error("Invalid pcap capture. {0}")]
+    InvalidPcap(String),
+}
+
+/// Error parsing a field of a record, with where it was found and the
+/// text that failed.
+#[derive(Debug, PartialEq)]
+pub struct FieldError {
+    /// Index of the record in the message, the header being 0. `None`
+    /// when the record was parsed on its own.
+    pub record: Option<usize>,
+    pub record_type: char,
+    pub field: usize,
+    pub component: Option<usize>,
+    pub text: String,
+    pub error: ASTMError,
+}
+
+impl FieldError {
+    pub(crate) fn new(record_type: char, field: usize, text: &str, error: ASTMError) -> Self {
+        let (component, text, error) = match error {
+            ASTMError::InvalidComponent {
+                component,
+                text,
+                error,
+            } => (Some(component), text, *error),
+            t => (None, text.to_string(), t),
+        };
+
+        Self {
+            record: None,
+            record_type,
+            field,
+            component,
+            text,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.record {
+            Some(t) => write!(f, "Record {} ({}), ", t, self.record_type)?,
+            None => write!(f, "{} record, ", self.record_type)?,
+        }
+        write!(f, "field {}", self.field)?;
+        if let Some(t) = self.component {
+            write!(f, ", component {}", t)?;
+        }
+        write!(f, " {:?}: {}", self.text, self.error)
+    }
 }
\ No newline at end of file
@@ -1,23 +1,44 @@
 #![forbid(unsafe_code)]
 
 use async_trait::async_trait;
-use log::error;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 
+use crate::values::TestIdLayout;
+
+mod charset;
+mod delimiters;
+mod driver;
 mod error;
+mod link;
 mod message;
-mod values;
+mod pcap;
+mod quirks;
+pub mod records;
+mod replay;
+pub mod values;
 mod socket;
+mod token;
+pub mod trace;
 #[cfg(test)]
 mod tests;
 
-pub use error::ASTMError;
+pub use error::{ASTMError, FieldError};
 pub type Result<T> = std::result::Result<T, ASTMError>;
 
-pub use message::{Frame, Message};
+pub use charset::CharEncoding;
+pub use delimiters::Delimiters;
+pub use link::{
+    Clock, DataLink, Input, LinkMode, ManualClock, Output, Role, Settings, State, Timer, TokioClock,
+};
+pub use message::{Frame, Message, Priority};
+pub use quirks::{Deviation, Deviations, Quirks};
+pub use records::{MessageTree, Record, Records};
+pub use replay::Recording;
 pub use socket::server::SocketServer;
+pub use token::{Token, Tokenizer};
+pub use trace::{Direction, Entry, Trace};
 
 #[macro_export]
 macro_rules! ctrl {
@@ -26,12 +47,6 @@ macro_rules! ctrl {
     };
 }
 
-macro_rules! some_ctrl {
-    ($x: ident) => {
-        Some(vec![CtrlChar::$x])
-    };
-}
-
 pub struct CtrlChar {}
 
 impl CtrlChar {
@@ -46,210 +61,24 @@ impl CtrlChar {
     const LF: u8 = 0x0A;
 }
 
-#[derive(Clone)]
-pub enum CharEncoding {
-    ASCII,
-    Windows1251,
-    UTF8,
-}
-
-#[derive(Clone, PartialEq)]
-enum State {
-    Idle,
-    Receiving,
-    Sending,
-}
-
-impl Default for State {
-    fn default() -> Self {
-        Self::Idle
-    }
-}
-
-#[derive(Clone, Default)]
-struct DataLink {
-    state: Arc<Mutex<State>>,
-    in_message: Arc<Mutex<Message>>,
-    out_message: Arc<Mutex<Message>>,
-    timeout: Arc<Mutex<Option<u64>>>,
-}
-
-impl DataLink {
-    async fn get_state(&self) -> State {
-        let state = self.state.lock().await;
-        (*state).to_owned()
-    }
-
-    async fn set_state(&self, src: State) {
-        let mut state = self.state.lock().await;
-        *state = src;
-    }
-
-    async fn get_in_message(&self) -> Message {
-        let in_message = self.in_message.lock().await;
-        (*in_message).to_owned()
-    }
-
-    async fn push_in_frame(&self, src: Frame) {
-        let mut in_message = self.in_message.lock().await;
-        (*in_message).push_frame(src);
-    }
-
-    async fn drop_in_message(&self) {
-        let mut in_message = self.in_message.lock().await;
-        *in_message = Message::default();
-    }
-
-    async fn set_out_message(&self, src: Message) {
-        let mut out_message = self.out_message.lock().await;
-        *out_message = src;
-    }
-
-    async fn is_out_message_empty(&self) -> bool {
-        let out_message = self.out_message.lock().await;
-        (*out_message).is_empty()
-    }
-
-    async fn pop_out_frame(&self) -> Option<Frame> {
-        let mut out_message = self.out_message.lock().await;
-        (*out_message).pop_frame()
-    }
-
-    async fn set_timeout(&self, src: u64) {
-        let mut timeout = self.timeout.lock().await;
-        *timeout = Some(src);
-    }
-
-    async fn reset_timeout(&self) {
-        let mut timeout = self.timeout.lock().await;
-        *timeout = None;
-    }
-
-    async fn is_timeout(&self) -> bool {
-        let mut timeout = self.timeout.lock().await;
-
-        match *timeout {
-            Some(t) if t == 0 => true,
-            Some(mut t) => {
-                t -= 1;
-                *timeout = Some(t);
-                false
-            }
-            None => false,
-        }
-    }
-}
-
-impl DataLink {
-    async fn read<S: Clone + Sync + Action<S>>(
-        &self,
-        src: &[u8],
-        astm: ASTM<S>,
-    ) -> Option<Vec<u8>> {
-        match self.get_state().await {
-            State::Idle => {
-                if src[0] == ctrl!(ENQ) {
-                    self.set_timeout(astm.timeout).await;
-                    self.set_state(State::Receiving).await;
-                    some_ctrl!(ACK)
-                } else {
-                    some_ctrl!(NAK)
-                }
-            }
-            State::Receiving => match Frame::deserialize(src, astm.encoding) {
-                Ok(frame) => {
-                    self.set_timeout(astm.timeout).await;
-                    let in_message = self.get_in_message().await;
-
-                    match astm.instrument.on_recv_frame(frame, &in_message).await {
-                        Ok(t) => {
-                            self.push_in_frame(t).await;
-                            some_ctrl!(ACK)
-                        }
-                        Err(err) => {
-                            error!("{}", err);
-                            some_ctrl!(NAK)
-                        }
-                    }
-                }
-                Err(err) => match src[0] {
-                    ctrl!(EOT) => {
-                        self.reset_timeout().await;
-                        self.set_state(State::Idle).await;
-
-                        let in_message = self.get_in_message().await;
-                        if let Some(t) = astm.instrument.on_recv_message(&in_message).await {
-                            self.set_out_message(t).await;
-                        }
-
-                        self.drop_in_message().await;
-
-                        None
-                    }
-                    _ => {
-                        self.set_timeout(astm.timeout).await;
-                        error!("{}", err);
-                        some_ctrl!(NAK)
-                    }
-                },
-            },
-            State::Sending => {
-                if src[0] == ctrl!(ACK) {
-                    match self.pop_out_frame().await {
-                        Some(t) => match t.serialize(astm.encoding) {
-                            Ok(t) => Some(t),
-                            Err(err) => {
-                                self.reset_timeout().await;
-                                self.set_state(State::Idle).await;
-
-                                error!("{}", err);
-                                some_ctrl!(NAK)
-                            }
-                        },
-                        None => some_ctrl!(EOT),
-                    }
-                } else {
-                    None
-                }
-            }
-        }
-    }
-
-    async fn control<S: Clone + Sync + Action<S>>(&self, astm: ASTM<S>) -> Option<Vec<u8>> {
-        sleep(Duration::from_secs(1)).await;
-
-        if self.is_timeout().await {
-            self.drop_in_message().await;
-            self.set_state(State::Idle).await;
-            some_ctrl!(NAK)
-        } else if self.get_state().await == State::Idle && !self.is_out_message_empty().await {
-            self.set_timeout(astm.timeout).await;
-            self.set_state(State::Sending).await;
-            some_ctrl!(ENQ)
-        } else {
-            None
-        }
-    }
-
-    async fn interval<S: Clone + Sync + Action<S>>(&self, astm: ASTM<S>) {
-        loop {
-            sleep(Duration::from_millis(astm.interval.unwrap())).await;
-
-            if let Some(t) = astm.instrument.on_idle_interval().await {
-                self.set_out_message(t).await;
-            }
-        }
-    }
-}
-
 #[async_trait]
 pub trait Action<I> {
-    async fn on_recv_frame(&self, frame: Frame, message: &Message) -> Result<Frame> {
+    async fn on_recv_frame(&self, frame: Frame, _message: &Message) -> Result<Frame> {
         Ok(frame)
     }
 
     async fn on_recv_message(&self, message: &Message) -> Option<Message>;
 
+    /// Called when a message could not be delivered after too many
+    /// retries and the transfer was aborted with `<EOT>`.
+    async fn on_send_abort(&self, _message: &Message) {}
+
+    /// Called every time the link changes its state.
+    async fn on_state_change(&self, _state: &State) {}
+
+    /// Called every time the number of messages waiting to be sent changes.
+    async fn on_queue_change(&self, _queued: usize) {}
+
     async fn on_idle_interval(&self) -> Option<Message> {
         None
     }
@@ -269,32 +98,132 @@ where
     I: Clone,
 {
     instrument: I,
-    timeout: u64,
     interval: Option<u64>,
-    encoding: CharEncoding,
+    settings: Settings,
+    trace: Option<Trace>,
 }
 
 impl<I: Clone> ASTM<I> {
     pub fn new(instrument: I) -> Self {
         Self {
             instrument,
-            timeout: 20,
             interval: None,
-            encoding: CharEncoding::ASCII,
+            settings: Settings::default(),
+            trace: None,
         }
     }
 
+    pub fn role(mut self, src: Role) -> Self {
+        self.settings.role = src;
+        self
+    }
+
+    pub fn link_mode(mut self, src: LinkMode) -> Self {
+        self.settings.mode = src;
+        self
+    }
+
+    /// Time waiting for `<ACK>` or `<NAK>` after `<ENQ>` or a frame (15 s).
+    pub fn reply_timeout(mut self, src: Duration) -> Self {
+        self.settings.reply_timeout = src;
+        self
+    }
+
+    /// Time the receiver waits for the next frame (30 s).
+    pub fn receive_timeout(mut self, src: Duration) -> Self {
+        self.settings.receive_timeout = src;
+        self
+    }
+
+    /// Time waiting to send `<ENQ>` again after the receiver is busy (10 s).
+    pub fn busy_delay(mut self, src: Duration) -> Self {
+        self.settings.busy_delay = src;
+        self
+    }
+
+    /// Time the host waits to send again after a line contention (20 s).
+    pub fn host_contention_delay(mut self, src: Duration) -> Self {
+        self.settings.host_contention_delay = src;
+        self
+    }
+
+    /// Time the instrument waits to send again after a line contention (1 s).
+    pub fn instrument_contention_delay(mut self, src: Duration) -> Self {
+        self.settings.instrument_contention_delay = src;
+        self
+    }
+
     pub fn interval(mut self, src: u64) -> Self {
         self.interval = Some(src);
         self
     }
 
+    /// Maximum number of encoded data bytes in an outgoing frame.
+    /// Longer records are split in `<ETB>` terminated frames.
+    pub fn frame_size(mut self, src: usize) -> Self {
+        self.settings.frame_size = src.max(1);
+        self
+    }
+
     pub fn encoding(mut self, src: CharEncoding) -> Self {
-        self.encoding = src;
+        self.settings.encoding = src;
         self
     }
 
+    /// Framing deviations tolerated for the instrument, each one is
+    /// logged and counted in `deviations`.
+    pub fn quirks(mut self, src: Quirks) -> Self {
+        self.settings.quirks = src;
+        self
+    }
+
+    /// Deviations accepted on every connection.
+    pub fn deviations(&self) -> &Deviations {
+        &self.settings.deviations
+    }
+
+    /// Captures every byte of every connection in a trace file.
+    pub fn trace(mut self, src: Trace) -> Self {
+        self.trace = Some(src);
+        self
+    }
+
+    pub fn trace_path(&self) -> Option<&Path> {
+        self.trace.as_ref().map(Trace::path)
+    }
+
+    /// Names of the manufacturer components of the universal test IDs
+    /// sent by the instrument.
+    pub fn test_id_layout(mut self, src: TestIdLayout) -> Self {
+        self.settings.test_id_layout = src;
+        self
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
     pub async fn run<P: PhysicalLayer<I>>(self, physical_layer: P) -> Result<()> {
         physical_layer.run(self).await
     }
+
+    /// Runs the data link over any tokio reader and writer, such as a
+    /// serial port or a file, until the reader is closed.
+    pub async fn serve<R, W>(self, read: R, write: W) -> Result<()>
+    where
+        I: Sync + Action<I>,
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        driver::drive(read, write, self).await
+    }
+
+    /// Replays a recorded session through the data link and the
+    /// instrument actions, returning what the link sent back.
+    pub async fn replay(&self, recording: &Recording) -> Result<Vec<Entry>>
+    where
+        I: Sync + Action<I>,
+    {
+        replay::replay(recording, self).await
+    }
 }
@@ -0,0 +1,750 @@
+use log::{debug, error, info, warn};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::quirks::{Deviations, Quirks};
+use crate::values::TestIdLayout;
+use crate::{ctrl, ASTMError, CharEncoding, CtrlChar, Frame, Message, Result, Token, Tokenizer};
+
+/// Maximum number of data bytes in a frame, as defined by E1381.
+pub(crate) const FRAME_SIZE: usize = 240;
+/// Number of times a frame is resent before the transfer is aborted.
+const MAX_RETRIES: u8 = 6;
+/// Raw records longer than this are dropped if the `<CR>` never comes.
+const MAX_RECORD_SIZE: usize = 64000;
+/// Time waiting for a reply after sending `<ENQ>` or a frame.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(15);
+/// Time the receiver waits for the next frame or `<EOT>`.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time waiting before `<ENQ>` again after the receiver answered `<NAK>`.
+const BUSY_DELAY: Duration = Duration::from_secs(10);
+/// Time the host waits before sending again after a line contention.
+const HOST_CONTENTION_DELAY: Duration = Duration::from_secs(20);
+/// Time the instrument waits before sending again after a line contention.
+const INSTRUMENT_CONTENTION_DELAY: Duration = Duration::from_secs(1);
+
+/* Clock */
+
+/// Monotonic time source, in microseconds.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u64;
+}
+
+/// Clock following tokio time, so it also works on paused runtimes.
+pub struct TokioClock {
+    start: tokio::time::Instant,
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self {
+            start: tokio::time::Instant::now(),
+        }
+    }
+}
+
+impl TokioClock {
+    /// Tokio instant of a time given by `now`.
+    pub fn instant(&self, src: u64) -> tokio::time::Instant {
+        self.start + Duration::from_micros(src)
+    }
+}
+
+impl Clock for TokioClock {
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+}
+
+/// Clock moved by hand, for tests and replays.
+#[derive(Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    pub fn set(&self, src: u64) {
+        self.0.store(src, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, src: Duration) {
+        self.0.fetch_add(src.as_micros() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/* Settings */
+
+/// Side of the link we are playing. On line contention the host yields
+/// and the instrument keeps the priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Role {
+    #[default]
+    Host,
+    Instrument,
+}
+
+/// Framing of the messages on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LinkMode {
+    /// E1381 framing, with `<ENQ>`, checksummed frames and `<EOT>`.
+    #[default]
+    E1381,
+    /// Bare `<CR>` terminated records, a message ends with its `L`
+    /// record or `<EOT>`. Nothing is acknowledged.
+    Raw,
+}
+
+/// Link parameters, set through the `ASTM` builder.
+#[derive(Clone)]
+pub struct Settings {
+    pub(crate) role: Role,
+    pub(crate) mode: LinkMode,
+    pub(crate) reply_timeout: Duration,
+    pub(crate) receive_timeout: Duration,
+    pub(crate) busy_delay: Duration,
+    pub(crate) host_contention_delay: Duration,
+    pub(crate) instrument_contention_delay: Duration,
+    pub(crate) frame_size: usize,
+    pub(crate) encoding: CharEncoding,
+    pub(crate) quirks: Quirks,
+    pub(crate) deviations: Deviations,
+    pub(crate) test_id_layout: TestIdLayout,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            role: Role::default(),
+            mode: LinkMode::default(),
+            reply_timeout: REPLY_TIMEOUT,
+            receive_timeout: RECEIVE_TIMEOUT,
+            busy_delay: BUSY_DELAY,
+            host_contention_delay: HOST_CONTENTION_DELAY,
+            instrument_contention_delay: INSTRUMENT_CONTENTION_DELAY,
+            frame_size: FRAME_SIZE,
+            encoding: CharEncoding::ASCII,
+            quirks: Quirks::default(),
+            deviations: Deviations::default(),
+            test_id_layout: TestIdLayout::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn link_mode(&self) -> LinkMode {
+        self.mode
+    }
+
+    pub fn encoding(&self) -> &CharEncoding {
+        &self.encoding
+    }
+
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+
+    pub fn test_id_layout(&self) -> &TestIdLayout {
+        &self.test_id_layout
+    }
+}
+
+/* Data Link */
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum State {
+    #[default]
+    Idle,
+    Receiving,
+    Sending,
+    /// Both sides sent `<ENQ>` at once, waiting before sending again.
+    Contention,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timer {
+    /// Sender waiting for `<ACK>` or `<NAK>`.
+    Reply,
+    /// Receiver waiting for the next frame or `<EOT>`.
+    Receive,
+    /// Busy or contention delay before sending `<ENQ>` again.
+    HoldOff,
+}
+
+/// Events fed into the data link.
+#[derive(Debug)]
+pub enum Input {
+    /// Bytes received, cut anywhere.
+    Bytes(Vec<u8>),
+    /// A timer started by the data link expired.
+    Timer(Timer),
+    /// Message to be sent.
+    Queue(Message),
+    /// Answer to `Output::CheckFrame`.
+    FrameChecked(Result<Frame>),
+}
+
+/// Actions requested by the data link.
+#[derive(Debug, PartialEq)]
+pub enum Output {
+    Send(Vec<u8>),
+    /// Frame to be checked before `<ACK>`, with the message received so far.
+    /// No other input is processed until `Input::FrameChecked`.
+    CheckFrame(Frame, Message),
+    /// Message completely received.
+    Deliver(Message),
+    /// Message given up after too many retries.
+    Abort(Message),
+    /// Timer to expire at the given time of the clock.
+    StartTimer(Timer, u64),
+    StopTimer(Timer),
+    State(State),
+    /// Number of messages waiting to be sent.
+    Queued(usize),
+}
+
+#[derive(Clone, Default)]
+struct Transfer {
+    // Message being sent.
+    message: Message,
+    // Frames not sent yet.
+    pending: Message,
+    // Last frame sent, waiting for a reply.
+    frame: Option<Frame>,
+    // Number of times the last frame has been resent.
+    retries: u8,
+}
+
+/// E1381 data link state machine, or its raw records counterpart.
+///
+/// It does no IO: inputs go in through `handle` and the actions to take
+/// come out. Timers are deadlines on the given clock.
+pub struct DataLink {
+    settings: Settings,
+    clock: Arc<dyn Clock>,
+    tokenizer: Tokenizer,
+    // Raw mode bytes not part of a complete record yet.
+    records: Vec<u8>,
+    state: State,
+    in_message: Message,
+    out_queue: VecDeque<Message>,
+    transfer: Option<Transfer>,
+    timeout: Option<(Timer, u64)>,
+    hold_off: Option<u64>,
+    checking: bool,
+    outputs: Vec<Output>,
+}
+
+impl DataLink {
+    pub fn new(settings: Settings, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            tokenizer: Tokenizer::with_quirks(&settings.quirks),
+            settings,
+            clock,
+            records: vec![],
+            state: State::default(),
+            in_message: Message::default(),
+            out_queue: VecDeque::new(),
+            transfer: None,
+            timeout: None,
+            hold_off: None,
+            checking: false,
+            outputs: vec![],
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn queued(&self) -> usize {
+        self.out_queue.len()
+    }
+
+    /// Framing deviations accepted so far.
+    pub fn deviations(&self) -> &Deviations {
+        &self.settings.deviations
+    }
+
+    /// Nearest timer to expire.
+    pub fn next_timer(&self) -> Option<(Timer, u64)> {
+        match (self.timeout, self.hold_off) {
+            (Some(a), Some(b)) if b < a.1 => Some((Timer::HoldOff, b)),
+            (Some(a), _) => Some(a),
+            (None, Some(b)) => Some((Timer::HoldOff, b)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn handle(&mut self, input: Input) -> Vec<Output> {
+        match input {
+            Input::Bytes(t) => match self.settings.mode {
+                LinkMode::E1381 => self.tokenizer.push(&t),
+                LinkMode::Raw => self.records.extend_from_slice(&t),
+            },
+            Input::Timer(t) => self.on_timer(t),
+            Input::Queue(t) => self.push_out_message(t),
+            Input::FrameChecked(t) => self.on_frame_checked(t),
+        }
+
+        match self.settings.mode {
+            LinkMode::E1381 => self.process_tokens(),
+            LinkMode::Raw => self.process_records(),
+        }
+
+        self.try_send();
+
+        std::mem::take(&mut self.outputs)
+    }
+}
+
+impl DataLink {
+    fn send(&mut self, src: Vec<u8>) {
+        self.outputs.push(Output::Send(src));
+    }
+
+    fn send_ctrl(&mut self, src: u8) {
+        self.send(vec![src]);
+    }
+
+    fn set_state(&mut self, src: State) {
+        if self.state != src {
+            debug!("Link state changed from {:?} to {:?}.", self.state, src);
+            self.state = src.clone();
+            self.outputs.push(Output::State(src));
+        }
+    }
+
+    fn set_timeout(&mut self, timer: Timer, src: Duration) {
+        let deadline = self.clock.now() + src.as_micros() as u64;
+        self.timeout = Some((timer, deadline));
+        self.outputs.push(Output::StartTimer(timer, deadline));
+    }
+
+    fn reset_timeout(&mut self) {
+        if let Some((timer, _)) = self.timeout.take() {
+            self.outputs.push(Output::StopTimer(timer));
+        }
+    }
+
+    fn set_hold_off(&mut self, src: Duration) {
+        let deadline = self.clock.now() + src.as_micros() as u64;
+        self.hold_off = Some(deadline);
+        self.outputs
+            .push(Output::StartTimer(Timer::HoldOff, deadline));
+    }
+
+    // Messages are queued by priority, FIFO between the same priority.
+    fn push_out_message(&mut self, src: Message) {
+        if src.is_empty() {
+            return;
+        }
+
+        let index = self
+            .out_queue
+            .iter()
+            .position(|t| t.priority > src.priority)
+            .unwrap_or(self.out_queue.len());
+        self.out_queue.insert(index, src);
+
+        self.outputs.push(Output::Queued(self.out_queue.len()));
+    }
+
+    // Puts the message back on the head of the queue, to be sent again.
+    fn restore_transfer(&mut self) {
+        if let Some(t) = self.transfer.take() {
+            self.out_queue.push_front(t.message);
+            self.outputs.push(Output::Queued(self.out_queue.len()));
+        }
+    }
+
+    fn process_tokens(&mut self) {
+        while !self.checking {
+            match self.tokenizer.next() {
+                Some(t) => self.on_token(t),
+                None => break,
+            }
+        }
+    }
+
+    fn on_token(&mut self, src: Token) {
+        match self.state {
+            State::Idle | State::Contention => {
+                if src == Token::ENQ {
+                    self.set_timeout(Timer::Receive, self.settings.receive_timeout);
+                    self.set_state(State::Receiving);
+                    self.send_ctrl(ctrl!(ACK));
+                } else {
+                    self.send_ctrl(ctrl!(NAK));
+                }
+            }
+            State::Receiving => match src {
+                Token::Frame(t) => match Frame::deserialize(
+                    &t,
+                    self.settings.encoding.clone(),
+                    &self.settings.quirks,
+                ) {
+                    Ok((frame, deviations)) => {
+                        for deviation in deviations {
+                            warn!("Accepted frame {} with {}.", frame.number, deviation);
+                            self.settings.deviations.add(deviation);
+                        }
+
+                        self.on_frame(frame)
+                    }
+                    Err(err) => {
+                        self.set_timeout(Timer::Receive, self.settings.receive_timeout);
+                        error!("{}", err);
+                        self.send_ctrl(ctrl!(NAK));
+                    }
+                },
+                Token::EOT => self.deliver(),
+                t => {
+                    self.set_timeout(Timer::Receive, self.settings.receive_timeout);
+                    error!("Unexpected {:?} while receiving a message.", t);
+                    self.send_ctrl(ctrl!(NAK));
+                }
+            },
+            State::Sending => {
+                let waiting_enq = matches!(&self.transfer, Some(t) if t.frame.is_none());
+
+                match src {
+                    Token::ACK => self.send_next_frame(),
+                    Token::NAK if waiting_enq => {
+                        // the receiver is busy, <ENQ> will be sent again
+                        // after the busy delay.
+                        warn!("Receiver not ready, <ENQ> rejected.");
+                        self.reset_timeout();
+                        self.restore_transfer();
+                        self.set_hold_off(self.settings.busy_delay);
+                        self.set_state(State::Idle);
+                    }
+                    Token::NAK => self.resend(),
+                    Token::ENQ if waiting_enq => {
+                        let delay = match self.settings.role {
+                            Role::Host => self.settings.host_contention_delay,
+                            Role::Instrument => self.settings.instrument_contention_delay,
+                        };
+
+                        warn!(
+                            "Line contention, {:?} waits {:?} before sending again.",
+                            self.settings.role, delay
+                        );
+
+                        self.reset_timeout();
+                        self.restore_transfer();
+                        self.set_hold_off(delay);
+                        self.set_state(State::Contention);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn deliver(&mut self) {
+        self.reset_timeout();
+
+        if self.hold_off.is_some() {
+            self.set_state(State::Contention);
+        } else {
+            self.set_state(State::Idle);
+        }
+
+        let message = std::mem::take(&mut self.in_message);
+        self.outputs.push(Output::Deliver(message));
+    }
+
+    fn on_frame(&mut self, frame: Frame) {
+        self.set_timeout(Timer::Receive, self.settings.receive_timeout);
+
+        match self.in_message.check_frame_number(&frame) {
+            Ok(()) => {
+                self.checking = true;
+                let message = self.in_message.clone();
+                self.outputs.push(Output::CheckFrame(frame, message));
+            }
+            // our <ACK> was lost, the sender repeats the frame.
+            Err(err @ ASTMError::DuplicateFrame(_)) => {
+                warn!("{}", err);
+                self.send_ctrl(ctrl!(ACK));
+            }
+            Err(err) => {
+                error!("{}", err);
+                self.send_ctrl(ctrl!(NAK));
+            }
+        }
+    }
+
+    fn on_frame_checked(&mut self, src: Result<Frame>) {
+        if !self.checking {
+            return;
+        }
+
+        self.checking = false;
+
+        match (src, self.settings.mode) {
+            (Ok(t), LinkMode::E1381) => {
+                self.in_message.push_frame(t);
+                self.send_ctrl(ctrl!(ACK));
+            }
+            (Err(err), LinkMode::E1381) => {
+                error!("{}", err);
+                self.send_ctrl(ctrl!(NAK));
+            }
+            (Ok(t), LinkMode::Raw) => {
+                let last = is_terminator(&t, &self.in_message);
+                self.in_message.push_frame(t);
+
+                if last {
+                    self.deliver();
+                }
+            }
+            (Err(err), LinkMode::Raw) => error!("{} Record dropped.", err),
+        }
+    }
+
+    fn on_timer(&mut self, src: Timer) {
+        let now = self.clock.now();
+
+        match src {
+            Timer::HoldOff => match self.hold_off {
+                Some(t) if t <= now => {
+                    self.hold_off = None;
+
+                    if self.state == State::Contention {
+                        info!("Line contention over.");
+                        self.set_state(State::Idle);
+                    }
+                }
+                _ => {}
+            },
+            timer => match self.timeout {
+                Some((t, deadline)) if t == timer && deadline <= now => {
+                    self.timeout = None;
+
+                    if self.state == State::Sending {
+                        self.resend();
+                    } else {
+                        warn!("Timeout waiting for the next frame, message discarded.");
+                        self.in_message = Message::default();
+                        self.set_state(State::Idle);
+
+                        if self.settings.mode == LinkMode::E1381 {
+                            self.send_ctrl(ctrl!(NAK));
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn try_send(&mut self) {
+        if self.state != State::Idle
+            || self.hold_off.is_some()
+            || self.checking
+            || self.out_queue.is_empty()
+        {
+            return;
+        }
+
+        let message = self.out_queue.pop_front().unwrap_or_default();
+        self.outputs.push(Output::Queued(self.out_queue.len()));
+
+        if self.settings.mode == LinkMode::Raw {
+            return self.send_records(message);
+        }
+
+        match message.split(self.settings.frame_size, &self.settings.encoding) {
+            Ok(pending) => {
+                self.transfer = Some(Transfer {
+                    message,
+                    pending,
+                    ..Default::default()
+                });
+                self.set_timeout(Timer::Reply, self.settings.reply_timeout);
+                self.set_state(State::Sending);
+                self.send_ctrl(ctrl!(ENQ));
+            }
+            Err(err) => {
+                error!("{}", err);
+                self.outputs.push(Output::Abort(message));
+            }
+        }
+    }
+
+    fn send_next_frame(&mut self) {
+        let frame = self.transfer.as_mut().and_then(|t| {
+            t.frame = t.pending.pop_frame();
+            t.retries = 0;
+            t.frame.clone()
+        });
+
+        match frame {
+            Some(t) => self.send_frame(t),
+            None => {
+                self.reset_timeout();
+                self.transfer = None;
+                self.set_state(State::Idle);
+                self.send_ctrl(ctrl!(EOT));
+            }
+        }
+    }
+
+    fn send_frame(&mut self, src: Frame) {
+        match src.serialize(self.settings.encoding.clone()) {
+            Ok(t) => {
+                self.set_timeout(Timer::Reply, self.settings.reply_timeout);
+                self.send(t);
+            }
+            Err(err) => {
+                error!("{}", err);
+                self.abort();
+            }
+        }
+    }
+
+    fn resend(&mut self) {
+        let (frame, retries) = match self.transfer.as_mut() {
+            Some(t) => {
+                t.retries += 1;
+                (t.frame.clone(), t.retries)
+            }
+            None => return,
+        };
+
+        if retries >= MAX_RETRIES {
+            error!("Too many failed retries, aborting transfer.");
+            return self.abort();
+        }
+
+        match frame {
+            Some(t) => {
+                warn!(
+                    "Resending frame {} ({}/{}).",
+                    t.number(),
+                    retries,
+                    MAX_RETRIES
+                );
+                self.send_frame(t);
+            }
+            None => {
+                warn!("Resending <ENQ> ({}/{}).", retries, MAX_RETRIES);
+                self.set_timeout(Timer::Reply, self.settings.reply_timeout);
+                self.send_ctrl(ctrl!(ENQ));
+            }
+        }
+    }
+
+    fn abort(&mut self) {
+        self.reset_timeout();
+        self.set_state(State::Idle);
+
+        if let Some(t) = self.transfer.take() {
+            self.outputs.push(Output::Abort(t.message));
+        }
+
+        self.send_ctrl(ctrl!(EOT));
+    }
+}
+
+/* Raw records */
+
+impl DataLink {
+    fn process_records(&mut self) {
+        while !self.checking {
+            let end = self
+                .records
+                .iter()
+                .position(|t| *t == ctrl!(CR) || *t == ctrl!(EOT));
+
+            let end = match end {
+                Some(t) => t,
+                None => {
+                    if self.records.len() > MAX_RECORD_SIZE {
+                        error!("Record too long, {} bytes dropped.", self.records.len());
+                        self.records.clear();
+                    }
+                    break;
+                }
+            };
+
+            // line feeds and whitespace between records are dropped
+            let start = self.records[..end]
+                .iter()
+                .position(|t| !t.is_ascii_whitespace())
+                .unwrap_or(end);
+
+            if start < end {
+                // an <EOT> is left to end the message once the record is in
+                let record: Vec<u8> = self.records.drain(..end).skip(start).collect();
+                self.drain_cr();
+                self.on_record(&record);
+            } else {
+                let terminator = self.records[end];
+                self.records.drain(..=end);
+
+                if terminator == ctrl!(EOT) && !self.in_message.is_empty() {
+                    self.deliver();
+                }
+            }
+        }
+    }
+
+    fn drain_cr(&mut self) {
+        if self.records.first() == Some(&ctrl!(CR)) {
+            self.records.remove(0);
+        }
+    }
+
+    fn on_record(&mut self, src: &[u8]) {
+        match self.settings.encoding.decode(src) {
+            Ok(mut data) => {
+                data.push('\r');
+                let frame = Frame {
+                    number: self.in_message.next_frame_number(),
+                    data,
+                    last: true,
+                };
+
+                self.set_timeout(Timer::Receive, self.settings.receive_timeout);
+                self.set_state(State::Receiving);
+                self.checking = true;
+                let message = self.in_message.clone();
+                self.outputs.push(Output::CheckFrame(frame, message));
+            }
+            Err(err) => error!("{} Record dropped.", err),
+        }
+    }
+
+    fn send_records(&mut self, message: Message) {
+        let mut dst = vec![];
+
+        for record in message.records() {
+            match self.settings.encoding.encode(&record) {
+                Ok(t) => dst.extend(t),
+                Err(err) => {
+                    error!("{}", err);
+                    return self.outputs.push(Output::Abort(message));
+                }
+            }
+            dst.push(ctrl!(CR));
+        }
+
+        self.send(dst);
+    }
+}
+
+// `L` record, using the field delimiter of the header if any.
+fn is_terminator(frame: &Frame, message: &Message) -> bool {
+    let delimiter = message.delimiters().unwrap_or_default().field;
+    let mut chars = frame.data.chars();
+
+    chars.next() == Some('L') && matches!(chars.next(), Some(t) if t == delimiter || t == '\r')
+}
@@ -1,10 +1,26 @@
-use encoding::all::{ASCII, WINDOWS_1251};
-use encoding::{DecoderTrap, EncoderTrap, Encoding};
 use std::str::FromStr;
 
-use crate::{ctrl, ASTMError, CharEncoding, CtrlChar, Result};
+use crate::link::FRAME_SIZE;
+use crate::quirks::{Deviation, Quirks};
+use crate::{ctrl, ASTMError, CharEncoding, CtrlChar, Delimiters, Result};
+
+// Uppercase hex digit, or lowercase if tolerated.
+fn checksum_digit(src: u8, quirks: &Quirks, deviations: &mut Vec<Deviation>) -> Option<u8> {
+    match src {
+        b'0'..=b'9' => Some(src - b'0'),
+        b'A'..=b'F' => Some(src - b'A' + 10),
+        b'a'..=b'f' if quirks.lowercase_checksum => {
+            if !deviations.contains(&Deviation::LowercaseChecksum) {
+                deviations.push(Deviation::LowercaseChecksum);
+            }
+            Some(src - b'a' + 10)
+        }
+        _ => None,
+    }
+}
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Frame {
     // Frame number.
     pub(crate) number: u8,
@@ -21,9 +37,16 @@ impl std::fmt::Display for Frame {
 }
 
 impl Frame {
-    pub(crate) fn deserialize(src: &[u8], encoding: CharEncoding) -> Result<Self> {
+    /// Deserializes a frame tolerating the given quirks, along with the
+    /// deviations that were accepted.
+    pub(crate) fn deserialize(
+        src: &[u8],
+        encoding: CharEncoding,
+        quirks: &Quirks,
+    ) -> Result<(Self, Vec<Deviation>)> {
         let mut checksum = 0usize;
         let mut frame = Frame::default();
+        let mut deviations = vec![];
         let mut chars = src.iter();
 
         // <STX>
@@ -68,68 +91,67 @@ impl Frame {
             return Err(ASTMError::OversizedMessage);
         }
 
-        frame.data = match encoding {
-            CharEncoding::ASCII => ASCII
-                .decode(&content, DecoderTrap::Strict)
-                .map_err(ASTMError::DecodingASCIIFrame)?,
-            CharEncoding::Windows1251 => WINDOWS_1251
-                .decode(&content, DecoderTrap::Strict)
-                .map_err(ASTMError::DecodingWINDOWS1251Frame)?,
-            CharEncoding::UTF8 => std::str::from_utf8(&content)
-                .map(String::from)
-                .map_err(ASTMError::DecodingUTF8Frame)?,
-        };
+        frame.data = encoding.decode(&content)?;
+
+        // Trailer, whitespace is stripped if tolerated
+        let mut trailer: Vec<u8> = chars.copied().collect();
+
+        if quirks.stray_whitespace && trailer.iter().any(|t| *t == b' ' || *t == b'\t') {
+            trailer.retain(|t| *t != b' ' && *t != b'\t');
+            deviations.push(Deviation::StrayWhitespace);
+        }
+
+        let mut chars = trailer.iter();
 
         // C1 Checksum
         let c1 = match chars.next() {
-            Some(t) => match char::from_u32(*t as u32) {
-                Some(char) => match char.to_digit(16) {
-                    Some(c1) => Ok(c1 as u8),
-                    None => Err(ASTMError::InvalidC1ChecksumValue),
-                },
-                None => Err(ASTMError::MissingC1ChecksumValue),
-            },
+            Some(t) => {
+                checksum_digit(*t, quirks, &mut deviations).ok_or(ASTMError::InvalidC1ChecksumValue)
+            }
             None => Err(ASTMError::MissingC1ChecksumValue),
         }?;
 
         // C2 Checksum
         let c2 = match chars.next() {
-            Some(t) => match char::from_u32(*t as u32) {
-                Some(char) => match char.to_digit(16) {
-                    Some(c2) => Ok(c2 as u8),
-                    None => Err(ASTMError::InvalidC2ChecksumValue),
-                },
-                None => Err(ASTMError::MissingC2ChecksumValue),
-            },
+            Some(t) => {
+                checksum_digit(*t, quirks, &mut deviations).ok_or(ASTMError::InvalidC2ChecksumValue)
+            }
             None => Err(ASTMError::MissingC2ChecksumValue),
         }?;
 
         // <CR>
         match chars.next() {
-            Some(t) if *t == ctrl!(CR) as u8 => Ok(()),
+            Some(t) if *t == ctrl!(CR) => Ok(()),
             Some(_) => Err(ASTMError::InvalidCRCharacter),
             None => Err(ASTMError::MissingCRCharacter),
         }?;
 
         // <LF>
         match chars.next() {
-            Some(t) if *t == ctrl!(LF) as u8 => Ok(()),
+            Some(t) if *t == ctrl!(LF) => Ok(()),
             Some(_) => Err(ASTMError::InvalidLFCharacter),
+            None if quirks.missing_lf => {
+                deviations.push(Deviation::MissingLF);
+                Ok(())
+            }
             None => Err(ASTMError::MissingLFCharacter),
         }?;
 
         // Validate frame
+        let received = c1 << 4 | c2;
         let modulo = (checksum % 256) as u8;
-        let text = format!("{:02X}", modulo);
 
-        let mut chars = text.chars();
-        let calc_c1 = chars.next().unwrap().to_digit(16).unwrap() as u8;
-        let calc_c2 = chars.next().unwrap().to_digit(16).unwrap() as u8;
+        if received == modulo {
+            return Ok((frame, deviations));
+        }
 
-        if c1 == calc_c1 && c2 == calc_c2 {
-            Ok(frame)
+        let without_number = modulo.wrapping_sub(frame.number + 0x30);
+
+        if quirks.checksum_without_frame_number && received == without_number {
+            deviations.push(Deviation::ChecksumWithoutFrameNumber);
+            Ok((frame, deviations))
         } else {
-            Err(ASTMError::DefectiveFrame(text))
+            Err(ASTMError::DefectiveFrame(format!("{:02X}", modulo)))
         }
     }
 
@@ -145,15 +167,7 @@ impl Frame {
         checksum += char as usize;
 
         // Data Content
-        let mut encoded = match encoding {
-            CharEncoding::ASCII => ASCII
-                .encode(&self.data, EncoderTrap::Strict)
-                .map_err(ASTMError::EncodingASCIIFrame)?,
-            CharEncoding::Windows1251 => WINDOWS_1251
-                .encode(&self.data, EncoderTrap::Strict)
-                .map_err(ASTMError::EncodingWINDOWS1251Frame)?,
-            CharEncoding::UTF8 => self.data.as_bytes().to_vec(),
-        };
+        let mut encoded = encoding.encode(&self.data)?;
 
         for char in &encoded {
             checksum += *char as usize;
@@ -199,16 +213,31 @@ impl Frame {
     }
 }
 
+/// Order in which queued messages are sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Priority {
+    Stat,
+    Asap,
+    #[default]
+    Routine,
+}
+
+/// Frames of a message. With the `serde` feature it is written as
+/// `{"frames": [{"number": 1, "data": "H|\\^&\r", "last": true}, ...],
+/// "priority": "Routine"}`.
 #[derive(Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message {
     pub(crate) frames: Vec<Frame>,
+    pub(crate) priority: Priority,
 }
 
 impl std::fmt::Display for Message {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         for frame in &self.frames {
             let value = frame.data();
-            fmt.write_str(&value)?
+            fmt.write_str(value)?
         }
 
         Ok(())
@@ -230,44 +259,136 @@ impl FromStr for Message {
     type Err = ASTMError;
 
     fn from_str(src: &str) -> std::result::Result<Self, Self::Err> {
-        let mut counter = 1;
-        let mut dst = Self::default();
+        Self::from_records(src.trim().split('\r'), FRAME_SIZE, &CharEncoding::UTF8)
+    }
+}
 
-        for full_line in src.trim().split('\r') {
-            let lines: Vec<&str> = if full_line.len() > 63900 {
-                full_line
-                    .as_bytes()
-                    .chunks(63900)
-                    .filter_map(|t| std::str::from_utf8(t).ok())
-                    .collect()
-            } else {
-                vec![full_line]
-            };
+// Splits a record in chunks of at most `frame_size` encoded bytes,
+// never cutting a character in half.
+fn split_record(src: &str, frame_size: usize, encoding: &CharEncoding) -> Result<Vec<String>> {
+    let mut dst = vec![];
+    let mut chunk = String::new();
+    let mut size = 0;
+    let mut buffer = [0u8; 4];
+
+    for char in src.chars() {
+        let len = encoding.encode(char.encode_utf8(&mut buffer))?.len();
+
+        if size + len > frame_size && !chunk.is_empty() {
+            dst.push(std::mem::take(&mut chunk));
+            size = 0;
+        }
+
+        chunk.push(char);
+        size += len;
+    }
+
+    if !chunk.is_empty() {
+        dst.push(chunk);
+    }
+
+    Ok(dst)
+}
+
+impl Message {
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn with_priority(mut self, src: Priority) -> Self {
+        self.priority = src;
+        self
+    }
+
+    /// Builds a message from records without their <CR> terminator.
+    /// Every record longer than `frame_size` is split in intermediate
+    /// frames ending with <ETB> and a last one ending with <ETX>.
+    pub(crate) fn from_records<'a>(
+        src: impl IntoIterator<Item = &'a str>,
+        frame_size: usize,
+        encoding: &CharEncoding,
+    ) -> Result<Self> {
+        let mut dst = Self::default();
+        let mut number = 1;
 
-            let size = lines.len();
+        for record in src {
+            let chunks = split_record(&format!("{}\r", record), frame_size, encoding)?;
+            let size = chunks.len();
 
-            for (index, line) in lines.into_iter().enumerate() {
+            for (index, data) in chunks.into_iter().enumerate() {
                 dst.push_frame(Frame {
-                    number: counter,
-                    data: format!("{}\r", line),
+                    number,
+                    data,
                     last: (index + 1 == size),
                 });
 
                 // 1, 2, .. , 7, 0, 1
-                counter += 1;
-                if counter == 8 {
-                    counter = 0;
-                }
+                number = (number + 1) % 8;
             }
         }
 
         Ok(dst)
     }
-}
 
-impl Message {
-    pub fn is_empty(&self) -> bool {
-        self.frames.is_empty()
+    /// Delimiters declared by the header, the first record of the message.
+    pub fn delimiters(&self) -> Result<Delimiters> {
+        let header: String = self
+            .frames
+            .iter()
+            .flat_map(|t| t.data.chars())
+            .take(6)
+            .collect();
+        Delimiters::from_header(&header)
+    }
+
+    /// Records of the message, rebuilt from their frames.
+    pub(crate) fn records(&self) -> Vec<String> {
+        let mut dst = vec![];
+        let mut record = String::new();
+
+        for frame in &self.frames {
+            record.push_str(&frame.data);
+
+            if frame.last {
+                let value = std::mem::take(&mut record);
+                dst.push(value.strip_suffix('\r').unwrap_or(&value).to_string());
+            }
+        }
+
+        if !record.is_empty() {
+            dst.push(record);
+        }
+
+        dst
+    }
+
+    /// Splits the message again for the given frame size and encoding.
+    pub(crate) fn split(&self, frame_size: usize, encoding: &CharEncoding) -> Result<Self> {
+        let records = self.records();
+        let dst = Self::from_records(records.iter().map(|t| t.as_str()), frame_size, encoding)?;
+        Ok(dst.with_priority(self.priority))
+    }
+
+    // 1, 2, .. , 7, 0, 1
+    pub(crate) fn next_frame_number(&self) -> u8 {
+        match self.frames.last() {
+            Some(t) => (t.number + 1) % 8,
+            None => 1,
+        }
+    }
+
+    pub(crate) fn check_frame_number(&self, frame: &Frame) -> Result<()> {
+        let expected = self.next_frame_number();
+
+        match self.frames.last() {
+            _ if frame.number == expected => Ok(()),
+            Some(t) if t.number == frame.number => Err(ASTMError::DuplicateFrame(frame.number)),
+            _ => Err(ASTMError::OutOfSequenceFrame(expected, frame.number)),
+        }
     }
 
     pub(crate) fn push_frame(&mut self, frame: Frame) {
@@ -275,7 +396,11 @@ impl Message {
     }
 
     pub(crate) fn pop_frame(&mut self) -> Option<Frame> {
-        self.frames.pop()
+        if self.frames.is_empty() {
+            None
+        } else {
+            Some(self.frames.remove(0))
+        }
     }
 }
 
@@ -0,0 +1,244 @@
+//! Minimal reader of TCP streams in libpcap captures.
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::trace::{Direction, Entry};
+use crate::{ASTMError, Result};
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+
+const TCP_SYN: u8 = 0x02;
+const TCP_ACK: u8 = 0x10;
+
+struct Reader<'a> {
+    src: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn u32(&self, index: usize) -> Result<u32> {
+        let bytes: [u8; 4] = self
+            .src
+            .get(index..index + 4)
+            .and_then(|t| t.try_into().ok())
+            .ok_or(ASTMError::InvalidPcap("Truncated capture.".to_string()))?;
+
+        Ok(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+}
+
+// Side of a connection, the client address and port.
+type Peer = (IpAddr, u16);
+
+#[derive(Default)]
+struct Stream {
+    connection: u64,
+    // Next expected sequence number by direction, to drop retransmissions.
+    next_in: Option<u32>,
+    next_out: Option<u32>,
+}
+
+/// Reads the payloads of the TCP connections to `port`. Bytes sent to
+/// the port are `in`, the ones sent from it are `out`.
+pub(crate) fn parse(src: &[u8], port: u16) -> Result<Vec<Entry>> {
+    let magic = src
+        .get(0..4)
+        .map(|t| u32::from_le_bytes([t[0], t[1], t[2], t[3]]))
+        .ok_or(ASTMError::InvalidPcap("Missing header.".to_string()))?;
+
+    let (big_endian, nanos) = match magic {
+        0xa1b2c3d4 => (false, false),
+        0xa1b23c4d => (false, true),
+        0xd4c3b2a1 => (true, false),
+        0x4d3cb2a1 => (true, true),
+        _ => return Err(ASTMError::InvalidPcap("Unknown file format.".to_string())),
+    };
+
+    let reader = Reader { src, big_endian };
+    let link_type = reader.u32(20)? & 0x0fff_ffff;
+    let mut index = 24;
+
+    let mut streams: HashMap<Peer, Stream> = HashMap::new();
+    let mut connections = 0;
+    let mut dst = vec![];
+
+    while index < src.len() {
+        let seconds = reader.u32(index)?;
+        let fraction = reader.u32(index + 4)?;
+        let size = reader.u32(index + 8)? as usize;
+        let packet = src
+            .get(index + 16..index + 16 + size)
+            .ok_or(ASTMError::InvalidPcap("Truncated packet.".to_string()))?;
+        index += 16 + size;
+
+        let nanos = match nanos {
+            true => fraction,
+            false => fraction.saturating_mul(1000),
+        };
+        let timestamp: DateTime<Utc> = Utc
+            .timestamp_opt(seconds as i64, nanos)
+            .single()
+            .ok_or(ASTMError::InvalidPcap("Invalid timestamp.".to_string()))?;
+
+        let segment = match ip_packet(packet, link_type, big_endian)?.and_then(tcp_segment) {
+            Some(t) => t,
+            None => continue,
+        };
+
+        let (direction, peer) = if segment.dst.1 == port {
+            (Direction::In, segment.src)
+        } else if segment.src.1 == port {
+            (Direction::Out, segment.dst)
+        } else {
+            continue;
+        };
+
+        let syn = segment.flags & TCP_SYN != 0;
+        let stream = streams.entry(peer).or_default();
+
+        // a new connection from the same port starts with a fresh stream
+        if stream.connection == 0 || (syn && segment.flags & TCP_ACK == 0) {
+            connections += 1;
+            *stream = Stream {
+                connection: connections,
+                ..Default::default()
+            };
+        }
+
+        let next = match direction {
+            Direction::In => &mut stream.next_in,
+            Direction::Out => &mut stream.next_out,
+        };
+
+        if syn {
+            *next = Some(segment.seq.wrapping_add(1));
+            continue;
+        }
+
+        let mut payload = segment.payload;
+
+        if let Some(expected) = *next {
+            let offset = expected.wrapping_sub(segment.seq) as i32;
+
+            if offset > 0 {
+                payload = payload.get(offset as usize..).unwrap_or_default();
+            }
+        }
+
+        if payload.is_empty() {
+            continue;
+        }
+
+        *next = Some(segment.seq.wrapping_add(segment.payload.len() as u32));
+
+        dst.push(Entry {
+            timestamp,
+            connection: stream.connection,
+            direction,
+            data: payload.to_vec(),
+        });
+    }
+
+    Ok(dst)
+}
+
+// Strips the link layer header.
+fn ip_packet(src: &[u8], link_type: u32, big_endian: bool) -> Result<Option<&[u8]>> {
+    let (header, ip) = match link_type {
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => (0, true),
+        LINKTYPE_NULL => {
+            let family = src.get(0..4).map(|t| match big_endian {
+                true => u32::from_be_bytes([t[0], t[1], t[2], t[3]]),
+                false => u32::from_le_bytes([t[0], t[1], t[2], t[3]]),
+            });
+            (4, matches!(family, Some(2 | 24 | 28 | 30)))
+        }
+        LINKTYPE_ETHERNET => {
+            let mut header = 14;
+            let mut ether_type = src.get(12..14).map(|t| u16::from_be_bytes([t[0], t[1]]));
+
+            // 802.1Q tag
+            if ether_type == Some(0x8100) {
+                header += 4;
+                ether_type = src.get(16..18).map(|t| u16::from_be_bytes([t[0], t[1]]));
+            }
+
+            (header, matches!(ether_type, Some(0x0800 | 0x86dd)))
+        }
+        LINKTYPE_LINUX_SLL => {
+            let protocol = src.get(14..16).map(|t| u16::from_be_bytes([t[0], t[1]]));
+            (16, matches!(protocol, Some(0x0800 | 0x86dd)))
+        }
+        _ => {
+            return Err(ASTMError::InvalidPcap(format!(
+                "Unsupported link type {}.",
+                link_type
+            )))
+        }
+    };
+
+    Ok(src.get(header..).filter(|_| ip))
+}
+
+struct Segment<'a> {
+    src: Peer,
+    dst: Peer,
+    seq: u32,
+    flags: u8,
+    payload: &'a [u8],
+}
+
+fn tcp_segment(src: &[u8]) -> Option<Segment<'_>> {
+    let (src_ip, dst_ip, tcp) = match src.first()? >> 4 {
+        4 => {
+            let header = ((src[0] & 0x0f) as usize) * 4;
+            let size = u16::from_be_bytes([*src.get(2)?, *src.get(3)?]) as usize;
+
+            if *src.get(9)? != 6 {
+                return None;
+            }
+
+            let src_ip: [u8; 4] = src.get(12..16)?.try_into().ok()?;
+            let dst_ip: [u8; 4] = src.get(16..20)?.try_into().ok()?;
+            // the total length drops the ethernet padding
+            let tcp = src.get(header..size.min(src.len()))?;
+            (IpAddr::from(src_ip), IpAddr::from(dst_ip), tcp)
+        }
+        6 => {
+            let size = u16::from_be_bytes([*src.get(4)?, *src.get(5)?]) as usize;
+
+            if *src.get(6)? != 6 {
+                return None;
+            }
+
+            let src_ip: [u8; 16] = src.get(8..24)?.try_into().ok()?;
+            let dst_ip: [u8; 16] = src.get(24..40)?.try_into().ok()?;
+            let tcp = src.get(40..(40 + size).min(src.len()))?;
+            (IpAddr::from(src_ip), IpAddr::from(dst_ip), tcp)
+        }
+        _ => return None,
+    };
+
+    let src_port = u16::from_be_bytes([*tcp.first()?, *tcp.get(1)?]);
+    let dst_port = u16::from_be_bytes([*tcp.get(2)?, *tcp.get(3)?]);
+    let seq = u32::from_be_bytes(tcp.get(4..8)?.try_into().ok()?);
+    let header = ((tcp.get(12)? >> 4) as usize) * 4;
+
+    Some(Segment {
+        src: (src_ip, src_port),
+        dst: (dst_ip, dst_port),
+        seq,
+        flags: *tcp.get(13)?,
+        payload: tcp.get(header..)?,
+    })
+}
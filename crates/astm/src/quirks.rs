@@ -0,0 +1,55 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Deviations from E1381 framing tolerated for an instrument.
+/// Everything is rejected by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Quirks {
+    /// Checksum written with lowercase hex digits.
+    pub lowercase_checksum: bool,
+    /// Frame ending at `<CR>`, without the `<LF>`.
+    pub missing_lf: bool,
+    /// Checksum that leaves out the frame number.
+    pub checksum_without_frame_number: bool,
+    /// Spaces or tabs around the checksum and the frame terminators.
+    pub stray_whitespace: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Deviation {
+    LowercaseChecksum,
+    MissingLF,
+    ChecksumWithoutFrameNumber,
+    StrayWhitespace,
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LowercaseChecksum => write!(f, "lowercase checksum"),
+            Self::MissingLF => write!(f, "missing <LF>"),
+            Self::ChecksumWithoutFrameNumber => write!(f, "checksum without frame number"),
+            Self::StrayWhitespace => write!(f, "stray whitespace"),
+        }
+    }
+}
+
+/// Accepted deviations, counted by kind. Clones share the counters, so
+/// every connection of an instrument adds to the same ones.
+#[derive(Clone, Debug, Default)]
+pub struct Deviations(Arc<[AtomicU64; 4]>);
+
+impl Deviations {
+    pub fn count(&self, src: Deviation) -> u64 {
+        self.0[src as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.0.iter().map(|t| t.load(Ordering::Relaxed)).sum()
+    }
+
+    pub(crate) fn add(&self, src: Deviation) {
+        self.0[src as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::records::{Fields, Writer};
+use crate::values::{CommentSource, CommentType};
+use crate::{Delimiters, Result};
+
+/// Comment Record (C), attached to the record before it.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommentRecord {
+    pub sequence_number: u32,
+    pub comment_source: Option<CommentSource>,
+    pub comment_text: Option<String>,
+    pub comment_type: Option<CommentType>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl CommentRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            comment_source: fields.value(2)?,
+            comment_text: fields.text(3),
+            comment_type: fields.value(4)?,
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('C', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.value(&self.comment_source);
+        dst.text(&self.comment_text);
+        dst.value(&self.comment_type);
+        dst.finish(self.fields)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::records::{Fields, Writer};
+use crate::values::{ASTMDateTime, ProcessingID};
+use crate::{Delimiters, Result};
+
+/// Message Header Record (H), the first record of every message.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageHeaderRecord {
+    pub delimiter_definition: Option<String>,
+    pub message_control_id: Option<String>,
+    pub access_password: Option<String>,
+    pub sender_name_or_id: Option<String>,
+    pub sender_street_address: Option<String>,
+    pub reserver_field: Option<String>,
+    pub sender_telephone_number: Option<String>,
+    pub characteristics_of_sender: Option<String>,
+    pub receiver_id: Option<String>,
+    pub comment_or_special_instructions: Option<String>,
+    pub processing_id: Option<ProcessingID>,
+    pub version_number: Option<String>,
+    pub date_and_time_of_message: Option<ASTMDateTime>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl MessageHeaderRecord {
+    /// Parses the header with the delimiters it declares.
+    pub fn parse(src: &str) -> Result<Self> {
+        let delimiters = Delimiters::from_header(src)?;
+        Self::from_fields(&Fields::new(src, &delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            delimiter_definition: fields.text(1),
+            message_control_id: fields.text(2),
+            access_password: fields.text(3),
+            sender_name_or_id: fields.text(4),
+            sender_street_address: fields.text(5),
+            reserver_field: fields.text(6),
+            sender_telephone_number: fields.text(7),
+            characteristics_of_sender: fields.text(8),
+            receiver_id: fields.text(9),
+            comment_or_special_instructions: fields.text(10),
+            processing_id: fields.value(11)?,
+            version_number: fields.text(12),
+            date_and_time_of_message: fields.value(13)?,
+            fields: fields.size(),
+        })
+    }
+
+    /// Serializes the header, declaring the given delimiters whatever
+    /// the `delimiter_definition` field holds.
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('H', delimiters);
+        dst.push(delimiters.definition());
+        dst.text(&self.message_control_id);
+        dst.text(&self.access_password);
+        dst.text(&self.sender_name_or_id);
+        dst.text(&self.sender_street_address);
+        dst.text(&self.reserver_field);
+        dst.text(&self.sender_telephone_number);
+        dst.text(&self.characteristics_of_sender);
+        dst.text(&self.receiver_id);
+        dst.text(&self.comment_or_special_instructions);
+        dst.value(&self.processing_id);
+        dst.text(&self.version_number);
+        dst.value(&self.date_and_time_of_message);
+        dst.finish(self.fields)
+    }
+}
@@ -0,0 +1,33 @@
+use crate::records::Fields;
+use crate::values::serialize_text;
+use crate::{Delimiters, Result};
+
+/// Manufacturer Information Record (M). Its fields are defined by each
+/// manufacturer, so they are kept as text.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManufacturerInformationRecord {
+    pub sequence_number: u32,
+    /// Fields after the sequence number.
+    pub fields: Vec<String>,
+}
+
+impl ManufacturerInformationRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            fields: fields.texts_from(2),
+        })
+    }
+
+    /// Serializes every field, the empty ones at the end too.
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = vec!["M".to_string(), self.sequence_number.to_string()];
+        dst.extend(self.fields.iter().map(|t| serialize_text(t, delimiters)));
+        dst.join(&delimiters.field.to_string())
+    }
+}
@@ -0,0 +1,468 @@
+//! Records of a message, parsed from the text of their fields.
+//!
+//! Fields are unescaped when parsed and escaped again when serialized.
+//! Text fields keep their repeats and components, separated by the
+//! delimiters of the message, so `&R&` and `&S&` are written back as the
+//! delimiters themselves. A `|` or a `&` in a text is written as `&F&`
+//! or `&E&`.
+//!
+//! With the `serde` feature, records are written as objects with the
+//! names of their fields, and a `type` field with the name of the
+//! `Record` variant:
+//!
+//! ```json
+//! {
+//!   "delimiters": { "field": "|", "repeat": "\\", "component": "^", "escape": "&" },
+//!   "records": [
+//!     { "type": "Patient", "sequence_number": 1, "patient_name": { "last_name": "DOE", ... }, ... },
+//!     { "type": "Result", "sequence_number": 1, "abnormal_flags": ["H", null, "W"], ... }
+//!   ]
+//! }
+//! ```
+//!
+//! Empty fields are `null`. Repeated fields are arrays, with `null` for
+//! empty repeats. Values of a single component, such as codes and
+//! dates, are strings with their text in the record, unescaped:
+//! `"F"`, `"20190821102030-0300"`. Structured values are objects with
+//! a field for each component.
+//!
+//! The layout that values alone do not tell is kept as well, so that
+//! records read back from JSON are written as they were parsed. When
+//! the last fields of a record or the last components of a value are
+//! empty, their number is in `fields` or `components`. A measurement
+//! written otherwise than its number, such as `170.0`, has it in `text`:
+//! `{ "measure": 170.0, "unit": "cm", "text": "170.0" }`. These fields
+//! are left out when not needed.
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+
+use crate::link::FRAME_SIZE;
+use crate::values::{parse_text, serialize_text, trailing};
+use crate::{ASTMError, CharEncoding, Delimiters, FieldError, Message, Result};
+
+mod comment;
+mod header;
+mod manufacturer;
+mod order;
+mod patient;
+mod request;
+mod result;
+mod scientific;
+mod terminator;
+mod tree;
+
+pub use crate::values::*;
+pub use comment::CommentRecord;
+pub use header::MessageHeaderRecord;
+pub use manufacturer::ManufacturerInformationRecord;
+pub use order::OrderRecord;
+pub use patient::PatientRecord;
+pub use request::RequestInformationRecord;
+pub use result::ResultRecord;
+pub use scientific::ScientificRecord;
+pub use terminator::MessageTerminatorRecord;
+pub use tree::{MessageTree, Node, OrderNode, PatientNode, Problem, StructureProblem};
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
+pub enum Record {
+    MessageHeader(MessageHeaderRecord),
+    Patient(PatientRecord),
+    Order(OrderRecord),
+    Result(ResultRecord),
+    Comment(CommentRecord),
+    RequestInformation(RequestInformationRecord),
+    MessageTerminator(MessageTerminatorRecord),
+    ManufacturerInformation(ManufacturerInformationRecord),
+    Scientific(ScientificRecord),
+}
+
+impl Record {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let delimiters = Self::delimiters(src, delimiters)?;
+        Self::from_fields(&Fields::new(src, &delimiters))
+    }
+
+    /// Parses a record, leaving empty the fields that fail to parse and
+    /// returning their errors with it. Fails only when the record type
+    /// or the delimiters of a header are not valid.
+    pub fn parse_lenient(src: &str, delimiters: &Delimiters) -> Result<(Self, Vec<FieldError>)> {
+        let delimiters = Self::delimiters(src, delimiters)?;
+        let fields = Fields::lenient(src, &delimiters);
+        let dst = Self::from_fields(&fields)?;
+
+        Ok((dst, fields.errors()))
+    }
+
+    // A header is parsed with the delimiters it declares. They come right
+    // after its type, so it is told apart by its first character rather
+    // than by a field split.
+    fn delimiters(src: &str, delimiters: &Delimiters) -> Result<Delimiters> {
+        let mut chars = src.chars().skip(1);
+        let header = src.starts_with('H') && chars.next().is_some_and(|t| !t.is_alphanumeric());
+
+        match header {
+            true => Delimiters::from_header(src),
+            false => Ok(*delimiters),
+        }
+    }
+
+    fn from_fields(fields: &Fields) -> Result<Self> {
+        match fields.fields[0] {
+            "H" => MessageHeaderRecord::from_fields(fields).map(Self::MessageHeader),
+            "P" => PatientRecord::from_fields(fields).map(Self::Patient),
+            "O" => OrderRecord::from_fields(fields).map(Self::Order),
+            "R" => ResultRecord::from_fields(fields).map(Self::Result),
+            "C" => CommentRecord::from_fields(fields).map(Self::Comment),
+            "Q" => RequestInformationRecord::from_fields(fields).map(Self::RequestInformation),
+            "L" => MessageTerminatorRecord::from_fields(fields).map(Self::MessageTerminator),
+            "M" => ManufacturerInformationRecord::from_fields(fields)
+                .map(Self::ManufacturerInformation),
+            "S" => ScientificRecord::from_fields(fields).map(Self::Scientific),
+            t => Err(ASTMError::InvalidRecordType(t.to_string())),
+        }
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        match self {
+            Self::MessageHeader(t) => t.serialize(delimiters),
+            Self::Patient(t) => t.serialize(delimiters),
+            Self::Order(t) => t.serialize(delimiters),
+            Self::Result(t) => t.serialize(delimiters),
+            Self::Comment(t) => t.serialize(delimiters),
+            Self::RequestInformation(t) => t.serialize(delimiters),
+            Self::MessageTerminator(t) => t.serialize(delimiters),
+            Self::ManufacturerInformation(t) => t.serialize(delimiters),
+            Self::Scientific(t) => t.serialize(delimiters),
+        }
+    }
+
+    pub fn record_type(&self) -> char {
+        match self {
+            Self::MessageHeader(_) => 'H',
+            Self::Patient(_) => 'P',
+            Self::Order(_) => 'O',
+            Self::Result(_) => 'R',
+            Self::Comment(_) => 'C',
+            Self::RequestInformation(_) => 'Q',
+            Self::MessageTerminator(_) => 'L',
+            Self::ManufacturerInformation(_) => 'M',
+            Self::Scientific(_) => 'S',
+        }
+    }
+}
+
+/// Records of a message, with the delimiters they are written with.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Records {
+    delimiters: Delimiters,
+    records: Vec<Record>,
+}
+
+impl Records {
+    pub fn new(records: Vec<Record>) -> Self {
+        Self {
+            delimiters: Delimiters::default(),
+            records,
+        }
+    }
+
+    pub fn with_delimiters(mut self, src: Delimiters) -> Self {
+        self.delimiters = src;
+        self
+    }
+
+    pub fn delimiters(&self) -> &Delimiters {
+        &self.delimiters
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Record> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn push(&mut self, src: Record) {
+        self.records.push(src);
+    }
+
+    /// Text of every record, without the `<CR>` terminator.
+    pub fn serialize(&self) -> Vec<String> {
+        self.records
+            .iter()
+            .map(|t| t.serialize(&self.delimiters))
+            .collect()
+    }
+
+    /// Parses the records of a message, collecting the errors of every
+    /// field instead of stopping at the first one. Fields that fail are
+    /// left empty, and records of an unknown type are left out.
+    pub fn parse_lenient(src: &Message) -> Result<(Self, Vec<FieldError>)> {
+        let delimiters = src.delimiters()?;
+        let mut dst = Self::new(vec![]).with_delimiters(delimiters);
+        let mut errors = vec![];
+
+        for (index, record) in indexed(&src.records()) {
+            match Record::parse_lenient(record, &delimiters) {
+                Ok((t, e)) => {
+                    dst.push(t);
+                    errors.extend(e.into_iter().map(|mut t| {
+                        t.record = Some(index);
+                        t
+                    }));
+                }
+                Err(t) => errors.push(field_error(t, index, record)),
+            }
+        }
+
+        Ok((dst, errors))
+    }
+}
+
+impl IntoIterator for Records {
+    type Item = Record;
+    type IntoIter = std::vec::IntoIter<Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Records {
+    type Item = &'a Record;
+    type IntoIter = std::slice::Iter<'a, Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+impl FromIterator<Record> for Records {
+    fn from_iter<T: IntoIterator<Item = Record>>(src: T) -> Self {
+        Self::new(src.into_iter().collect())
+    }
+}
+
+impl TryFrom<Message> for Records {
+    type Error = ASTMError;
+
+    fn try_from(src: Message) -> Result<Self> {
+        Self::try_from(&src)
+    }
+}
+
+impl TryFrom<&Message> for Records {
+    type Error = ASTMError;
+
+    /// Parses the records with the delimiters declared by the header.
+    /// Errors tell the index of the record that failed.
+    fn try_from(src: &Message) -> Result<Self> {
+        let delimiters = src.delimiters()?;
+        let records = indexed(&src.records())
+            .map(|(index, t)| Record::parse(t, &delimiters).map_err(|e| locate(e, index, t)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            delimiters,
+            records,
+        })
+    }
+}
+
+// Records of a message with their index, leaving out the empty ones.
+fn indexed(src: &[String]) -> impl Iterator<Item = (usize, &str)> {
+    src.iter()
+        .filter(|t| !t.is_empty())
+        .map(|t| t.as_str())
+        .enumerate()
+}
+
+// Error of a record with the index of the record. Errors that are not
+// about a field are about the whole record, at field 0.
+fn field_error(src: ASTMError, index: usize, record: &str) -> FieldError {
+    let mut dst = match src {
+        ASTMError::InvalidField(t) => *t,
+        t => {
+            let record_type = record.chars().next().unwrap_or_default();
+            FieldError::new(record_type, 0, record, t)
+        }
+    };
+
+    dst.record = Some(index);
+    dst
+}
+
+fn locate(src: ASTMError, index: usize, record: &str) -> ASTMError {
+    ASTMError::InvalidField(Box::new(field_error(src, index, record)))
+}
+
+impl TryFrom<&Records> for Message {
+    type Error = ASTMError;
+
+    fn try_from(src: &Records) -> Result<Self> {
+        let records = src.serialize();
+        Message::from_records(
+            records.iter().map(|t| t.as_str()),
+            FRAME_SIZE,
+            &CharEncoding::UTF8,
+        )
+    }
+}
+
+// Fields of a record, split by the field delimiter. The record type is
+// field 0. Lenient fields keep the errors and leave the field empty
+// instead of failing.
+pub(crate) struct Fields<'a> {
+    fields: Vec<&'a str>,
+    delimiters: &'a Delimiters,
+    errors: Option<RefCell<Vec<FieldError>>>,
+}
+
+impl<'a> Fields<'a> {
+    pub(crate) fn new(src: &'a str, delimiters: &'a Delimiters) -> Self {
+        Self {
+            fields: src.split(delimiters.field).collect(),
+            delimiters,
+            errors: None,
+        }
+    }
+
+    fn lenient(src: &'a str, delimiters: &'a Delimiters) -> Self {
+        Self {
+            errors: Some(RefCell::default()),
+            ..Self::new(src, delimiters)
+        }
+    }
+
+    fn record_type(&self) -> char {
+        self.fields[0].chars().next().unwrap_or_default()
+    }
+
+    fn get(&self, index: usize) -> Option<&'a str> {
+        self.fields.get(index).copied().filter(|t| !t.is_empty())
+    }
+
+    fn fail(&self, index: usize, src: &str, error: ASTMError) -> Result<()> {
+        let error = FieldError::new(self.record_type(), index, src, error);
+
+        match &self.errors {
+            Some(t) => {
+                t.borrow_mut().push(error);
+                Ok(())
+            }
+            None => Err(ASTMError::InvalidField(Box::new(error))),
+        }
+    }
+
+    /// Number of fields when the last ones are empty, 0 otherwise.
+    pub(crate) fn size(&self) -> usize {
+        trailing(&self.fields, |t| t.is_empty())
+    }
+
+    fn errors(self) -> Vec<FieldError> {
+        self.errors.map(RefCell::into_inner).unwrap_or_default()
+    }
+
+    /// Text of a field, unescaped, `None` when empty.
+    pub(crate) fn text(&self, index: usize) -> Option<String> {
+        self.get(index).map(|t| parse_text(t, self.delimiters))
+    }
+
+    /// Text of the fields from the index on, unescaped.
+    pub(crate) fn texts_from(&self, index: usize) -> Vec<String> {
+        self.fields
+            .iter()
+            .skip(index)
+            .map(|t| parse_text(t, self.delimiters))
+            .collect()
+    }
+
+    pub(crate) fn value<T: Value>(&self, index: usize) -> Result<Option<T>> {
+        let src = match self.get(index) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        match T::parse(src, self.delimiters) {
+            Ok(t) => Ok(Some(t)),
+            Err(t) => self.fail(index, src, t).map(|_| None),
+        }
+    }
+
+    /// Repeats of a field, empty when the field is empty.
+    pub(crate) fn repeated<T: Value>(&self, index: usize) -> Result<Repeated<T>> {
+        self.value(index).map(Option::unwrap_or_default)
+    }
+
+    /// Sequence number of the record, 0 when lenient fields fail to
+    /// parse it.
+    pub(crate) fn sequence_number(&self, index: usize) -> Result<u32> {
+        let src = self.get(index).unwrap_or_default();
+        let error = match src.parse() {
+            Ok(t) => return Ok(t),
+            Err(_) if src.is_empty() => ASTMError::MissingSequenceNumberValue,
+            Err(t) => ASTMError::ParseIntNumber(t),
+        };
+
+        self.fail(index, src, error).map(|_| 0)
+    }
+}
+
+// Builds the text of a record, field after field.
+pub(crate) struct Writer<'a> {
+    fields: Vec<String>,
+    delimiters: &'a Delimiters,
+}
+
+impl<'a> Writer<'a> {
+    pub(crate) fn new(record_type: char, delimiters: &'a Delimiters) -> Self {
+        Self {
+            fields: vec![record_type.to_string()],
+            delimiters,
+        }
+    }
+
+    pub(crate) fn push(&mut self, src: String) {
+        self.fields.push(src);
+    }
+
+    pub(crate) fn text(&mut self, src: &Option<String>) {
+        let value = src.as_deref().unwrap_or_default();
+        self.push(serialize_text(value, self.delimiters));
+    }
+
+    pub(crate) fn value<T: Value>(&mut self, src: &Option<T>) {
+        let value = src.as_ref().map(|t| t.serialize(self.delimiters));
+        self.push(value.unwrap_or_default());
+    }
+
+    pub(crate) fn repeated<T: Value>(&mut self, src: &Repeated<T>) {
+        self.push(src.serialize(self.delimiters));
+    }
+
+    /// Fields joined, leaving out the empty ones at the end past the
+    /// number of fields parsed.
+    pub(crate) fn finish(mut self, fields: usize) -> String {
+        let size = self
+            .fields
+            .iter()
+            .rposition(|t| !t.is_empty())
+            .map_or(0, |t| t + 1);
+
+        self.fields.resize(size.max(fields), String::new());
+        self.fields.join(&self.delimiters.field.to_string())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::records::{Fields, Writer};
+use crate::values::{
+    ASTMDateTime, ActionCode, Measurement, OrderPriority, Repeated, ReportType, SpecimenDescriptor,
+    SpecimenId, UniversalTestId,
+};
+use crate::{Delimiters, Result};
+
+/// Test Order Record (O).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderRecord {
+    pub sequence_number: u32,
+    pub specimen_id: Option<SpecimenId>,
+    pub instrument_specimen_id: Option<SpecimenId>,
+    pub universal_test_id: Repeated<UniversalTestId>,
+    pub priority: Option<OrderPriority>,
+    pub requested_date_and_time: Option<ASTMDateTime>,
+    pub specimen_collection_date_and_time: Option<ASTMDateTime>,
+    pub collection_end_time: Option<ASTMDateTime>,
+    pub collection_volume: Option<Measurement>,
+    pub collector_id: Option<String>,
+    pub action_code: Option<ActionCode>,
+    pub danger_code: Option<String>,
+    pub relevant_clinical_information: Option<String>,
+    pub date_and_time_specimen_received: Option<ASTMDateTime>,
+    pub specimen_descriptor: Option<SpecimenDescriptor>,
+    pub ordering_physician: Option<String>,
+    pub physicians_telephone_number: Option<String>,
+    pub user_field_no_1: Option<String>,
+    pub user_field_no_2: Option<String>,
+    pub laboratory_field_no_1: Option<String>,
+    pub laboratory_field_no_2: Option<String>,
+    pub date_and_time_results_reported: Option<ASTMDateTime>,
+    pub instrument_charge: Option<String>,
+    pub instrument_section_id: Option<String>,
+    pub report_type: Option<ReportType>,
+    pub reserved_field: Option<String>,
+    pub location_of_specimen_collection: Option<String>,
+    pub nosocomial_infection_flag: Option<String>,
+    pub specimen_service: Option<String>,
+    pub specimen_institution: Option<String>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl OrderRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            specimen_id: fields.value(2)?,
+            instrument_specimen_id: fields.value(3)?,
+            universal_test_id: fields.repeated(4)?,
+            priority: fields.value(5)?,
+            requested_date_and_time: fields.value(6)?,
+            specimen_collection_date_and_time: fields.value(7)?,
+            collection_end_time: fields.value(8)?,
+            collection_volume: fields.value(9)?,
+            collector_id: fields.text(10),
+            action_code: fields.value(11)?,
+            danger_code: fields.text(12),
+            relevant_clinical_information: fields.text(13),
+            date_and_time_specimen_received: fields.value(14)?,
+            specimen_descriptor: fields.value(15)?,
+            ordering_physician: fields.text(16),
+            physicians_telephone_number: fields.text(17),
+            user_field_no_1: fields.text(18),
+            user_field_no_2: fields.text(19),
+            laboratory_field_no_1: fields.text(20),
+            laboratory_field_no_2: fields.text(21),
+            date_and_time_results_reported: fields.value(22)?,
+            instrument_charge: fields.text(23),
+            instrument_section_id: fields.text(24),
+            report_type: fields.value(25)?,
+            reserved_field: fields.text(26),
+            location_of_specimen_collection: fields.text(27),
+            nosocomial_infection_flag: fields.text(28),
+            specimen_service: fields.text(29),
+            specimen_institution: fields.text(30),
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('O', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.value(&self.specimen_id);
+        dst.value(&self.instrument_specimen_id);
+        dst.repeated(&self.universal_test_id);
+        dst.value(&self.priority);
+        dst.value(&self.requested_date_and_time);
+        dst.value(&self.specimen_collection_date_and_time);
+        dst.value(&self.collection_end_time);
+        dst.value(&self.collection_volume);
+        dst.text(&self.collector_id);
+        dst.value(&self.action_code);
+        dst.text(&self.danger_code);
+        dst.text(&self.relevant_clinical_information);
+        dst.value(&self.date_and_time_specimen_received);
+        dst.value(&self.specimen_descriptor);
+        dst.text(&self.ordering_physician);
+        dst.text(&self.physicians_telephone_number);
+        dst.text(&self.user_field_no_1);
+        dst.text(&self.user_field_no_2);
+        dst.text(&self.laboratory_field_no_1);
+        dst.text(&self.laboratory_field_no_2);
+        dst.value(&self.date_and_time_results_reported);
+        dst.text(&self.instrument_charge);
+        dst.text(&self.instrument_section_id);
+        dst.value(&self.report_type);
+        dst.text(&self.reserved_field);
+        dst.text(&self.location_of_specimen_collection);
+        dst.text(&self.nosocomial_infection_flag);
+        dst.text(&self.specimen_service);
+        dst.text(&self.specimen_institution);
+        dst.finish(self.fields)
+    }
+}
@@ -0,0 +1,138 @@
+use crate::records::{Fields, Writer};
+use crate::values::{
+    ASTMDate, Address, AdmissionStatus, IsolationStatus, MaritalStatus, Measurement, PatientName,
+    PatientRace, PatientReligion, PatientSex, Repeated,
+};
+use crate::{Delimiters, Result};
+
+/// Patient Information Record (P).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatientRecord {
+    pub sequence_number: u32,
+    pub practice_assigned_patient_id: Option<String>,
+    pub laboratory_assigned_patient_id: Option<String>,
+    pub patient_id_no_3: Option<String>,
+    pub patient_name: Option<PatientName>,
+    pub mothers_maiden_name: Option<String>,
+    pub birthdate: Option<ASTMDate>,
+    pub patient_sex: Option<PatientSex>,
+    pub patient_race: Option<PatientRace>,
+    pub patient_address: Option<Address>,
+    pub reserved_field: Option<String>,
+    pub patient_telephone_number: Repeated<String>,
+    pub attending_physician_id: Repeated<String>,
+    pub special_field_1: Option<String>,
+    pub special_field_2: Option<String>,
+    pub patient_height: Option<Measurement>,
+    pub patient_weight: Option<Measurement>,
+    pub diagnosis: Repeated<String>,
+    pub active_medications: Repeated<String>,
+    pub patient_diet: Option<String>,
+    pub practice_field_no_1: Option<String>,
+    pub practice_field_no_2: Option<String>,
+    /// Admission date, then discharge date.
+    pub admission_and_discharge_dates: Repeated<ASTMDate>,
+    pub admission_status: Option<AdmissionStatus>,
+    pub location: Option<String>,
+    pub nature_of_alternative_diagnostic_code: Option<String>,
+    pub alternative_diagnostic_code: Repeated<String>,
+    pub patient_religion: Option<PatientReligion>,
+    pub marital_status: Option<MaritalStatus>,
+    pub isolation_status: Repeated<IsolationStatus>,
+    pub language: Option<String>,
+    pub hospital_service: Option<String>,
+    pub hospital_institution: Option<String>,
+    pub dosage_category: Option<String>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl PatientRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            practice_assigned_patient_id: fields.text(2),
+            laboratory_assigned_patient_id: fields.text(3),
+            patient_id_no_3: fields.text(4),
+            patient_name: fields.value(5)?,
+            mothers_maiden_name: fields.text(6),
+            birthdate: fields.value(7)?,
+            patient_sex: fields.value(8)?,
+            patient_race: fields.value(9)?,
+            patient_address: fields.value(10)?,
+            reserved_field: fields.text(11),
+            patient_telephone_number: fields.repeated(12)?,
+            attending_physician_id: fields.repeated(13)?,
+            special_field_1: fields.text(14),
+            special_field_2: fields.text(15),
+            patient_height: fields.value(16)?,
+            patient_weight: fields.value(17)?,
+            diagnosis: fields.repeated(18)?,
+            active_medications: fields.repeated(19)?,
+            patient_diet: fields.text(20),
+            practice_field_no_1: fields.text(21),
+            practice_field_no_2: fields.text(22),
+            admission_and_discharge_dates: fields.repeated(23)?,
+            admission_status: fields.value(24)?,
+            location: fields.text(25),
+            nature_of_alternative_diagnostic_code: fields.text(26),
+            alternative_diagnostic_code: fields.repeated(27)?,
+            patient_religion: fields.value(28)?,
+            marital_status: fields.value(29)?,
+            isolation_status: fields.repeated(30)?,
+            language: fields.text(31),
+            hospital_service: fields.text(32),
+            hospital_institution: fields.text(33),
+            dosage_category: fields.text(34),
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('P', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.text(&self.practice_assigned_patient_id);
+        dst.text(&self.laboratory_assigned_patient_id);
+        dst.text(&self.patient_id_no_3);
+        dst.value(&self.patient_name);
+        dst.text(&self.mothers_maiden_name);
+        dst.value(&self.birthdate);
+        dst.value(&self.patient_sex);
+        dst.value(&self.patient_race);
+        dst.value(&self.patient_address);
+        dst.text(&self.reserved_field);
+        dst.repeated(&self.patient_telephone_number);
+        dst.repeated(&self.attending_physician_id);
+        dst.text(&self.special_field_1);
+        dst.text(&self.special_field_2);
+        dst.value(&self.patient_height);
+        dst.value(&self.patient_weight);
+        dst.repeated(&self.diagnosis);
+        dst.repeated(&self.active_medications);
+        dst.text(&self.patient_diet);
+        dst.text(&self.practice_field_no_1);
+        dst.text(&self.practice_field_no_2);
+        dst.repeated(&self.admission_and_discharge_dates);
+        dst.value(&self.admission_status);
+        dst.text(&self.location);
+        dst.text(&self.nature_of_alternative_diagnostic_code);
+        dst.repeated(&self.alternative_diagnostic_code);
+        dst.value(&self.patient_religion);
+        dst.value(&self.marital_status);
+        dst.repeated(&self.isolation_status);
+        dst.text(&self.language);
+        dst.text(&self.hospital_service);
+        dst.text(&self.hospital_institution);
+        dst.text(&self.dosage_category);
+        dst.finish(self.fields)
+    }
+}
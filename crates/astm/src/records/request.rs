@@ -0,0 +1,68 @@
+use crate::records::{Fields, Writer};
+use crate::values::{ASTMDateTime, RangeId, Repeated, RequestStatus, UniversalTestId};
+use crate::{Delimiters, Result};
+
+/// Request Information Record (Q), a query for orders or results.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RequestInformationRecord {
+    pub sequence_number: u32,
+    pub starting_range_id: Option<RangeId>,
+    pub ending_range_id: Option<RangeId>,
+    pub universal_test_id: Repeated<UniversalTestId>,
+    pub nature_of_request_time_limits: Option<String>,
+    pub beginning_request_results_date_and_time: Option<ASTMDateTime>,
+    pub ending_request_results_date_and_time: Option<ASTMDateTime>,
+    pub requesting_physician_name: Option<String>,
+    pub requesting_physician_telephone_number: Option<String>,
+    pub user_field_no_1: Option<String>,
+    pub user_field_no_2: Option<String>,
+    pub request_information_status_codes: Repeated<RequestStatus>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl RequestInformationRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            starting_range_id: fields.value(2)?,
+            ending_range_id: fields.value(3)?,
+            universal_test_id: fields.repeated(4)?,
+            nature_of_request_time_limits: fields.text(5),
+            beginning_request_results_date_and_time: fields.value(6)?,
+            ending_request_results_date_and_time: fields.value(7)?,
+            requesting_physician_name: fields.text(8),
+            requesting_physician_telephone_number: fields.text(9),
+            user_field_no_1: fields.text(10),
+            user_field_no_2: fields.text(11),
+            request_information_status_codes: fields.repeated(12)?,
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('Q', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.value(&self.starting_range_id);
+        dst.value(&self.ending_range_id);
+        dst.repeated(&self.universal_test_id);
+        dst.text(&self.nature_of_request_time_limits);
+        dst.value(&self.beginning_request_results_date_and_time);
+        dst.value(&self.ending_request_results_date_and_time);
+        dst.text(&self.requesting_physician_name);
+        dst.text(&self.requesting_physician_telephone_number);
+        dst.text(&self.user_field_no_1);
+        dst.text(&self.user_field_no_2);
+        dst.repeated(&self.request_information_status_codes);
+        dst.finish(self.fields)
+    }
+}
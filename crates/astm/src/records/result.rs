@@ -0,0 +1,72 @@
+use crate::records::{Fields, Writer};
+use crate::values::{ASTMDateTime, AbnormalFlag, Repeated, ResultStatus, UniversalTestId};
+use crate::{Delimiters, Result};
+
+/// Result Record (R).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResultRecord {
+    pub sequence_number: u32,
+    pub universal_test_id: Option<UniversalTestId>,
+    /// Value as text, numeric or not, such as `1.25`, `<0.01` or `POS`.
+    pub measurement_value: Option<String>,
+    pub units: Option<String>,
+    pub reference_ranges: Option<String>,
+    pub abnormal_flags: Repeated<AbnormalFlag>,
+    pub nature_of_abnormality_testing: Option<String>,
+    pub result_status: Option<ResultStatus>,
+    pub date_of_change_in_normative_values: Option<ASTMDateTime>,
+    pub operator_identification: Option<String>,
+    pub date_and_time_test_started: Option<ASTMDateTime>,
+    pub date_and_time_test_completed: Option<ASTMDateTime>,
+    pub instrument_identification: Option<String>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl ResultRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            universal_test_id: fields.value(2)?,
+            measurement_value: fields.text(3),
+            units: fields.text(4),
+            reference_ranges: fields.text(5),
+            abnormal_flags: fields.repeated(6)?,
+            nature_of_abnormality_testing: fields.text(7),
+            result_status: fields.value(8)?,
+            date_of_change_in_normative_values: fields.value(9)?,
+            operator_identification: fields.text(10),
+            date_and_time_test_started: fields.value(11)?,
+            date_and_time_test_completed: fields.value(12)?,
+            instrument_identification: fields.text(13),
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('R', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.value(&self.universal_test_id);
+        dst.text(&self.measurement_value);
+        dst.text(&self.units);
+        dst.text(&self.reference_ranges);
+        dst.repeated(&self.abnormal_flags);
+        dst.text(&self.nature_of_abnormality_testing);
+        dst.value(&self.result_status);
+        dst.value(&self.date_of_change_in_normative_values);
+        dst.text(&self.operator_identification);
+        dst.value(&self.date_and_time_test_started);
+        dst.value(&self.date_and_time_test_completed);
+        dst.text(&self.instrument_identification);
+        dst.finish(self.fields)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::records::{Fields, Writer};
+use crate::values::{ASTMDate, ASTMDateTime, PatientRace, PatientSex, SpecimenDescriptor};
+use crate::{Delimiters, Result};
+
+/// Scientific Record (S).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScientificRecord {
+    pub sequence_number: u32,
+    pub analytical_method: Option<String>,
+    pub instrumentation: Option<String>,
+    pub reagents: Option<String>,
+    pub units_of_measure: Option<String>,
+    pub quality_control: Option<String>,
+    pub specimen_descriptor: Option<SpecimenDescriptor>,
+    pub reserved_field: Option<String>,
+    pub container: Option<String>,
+    pub specimen_id: Option<String>,
+    pub analyte: Option<String>,
+    pub result: Option<String>,
+    pub result_units: Option<String>,
+    pub collection_date_and_time: Option<ASTMDateTime>,
+    pub result_date_and_time: Option<ASTMDateTime>,
+    pub analytical_preprocessing_steps: Option<String>,
+    pub patient_diagnosis: Option<String>,
+    pub patient_birthdate: Option<ASTMDate>,
+    pub patient_sex: Option<PatientSex>,
+    pub patient_race: Option<PatientRace>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl ScientificRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            analytical_method: fields.text(2),
+            instrumentation: fields.text(3),
+            reagents: fields.text(4),
+            units_of_measure: fields.text(5),
+            quality_control: fields.text(6),
+            specimen_descriptor: fields.value(7)?,
+            reserved_field: fields.text(8),
+            container: fields.text(9),
+            specimen_id: fields.text(10),
+            analyte: fields.text(11),
+            result: fields.text(12),
+            result_units: fields.text(13),
+            collection_date_and_time: fields.value(14)?,
+            result_date_and_time: fields.value(15)?,
+            analytical_preprocessing_steps: fields.text(16),
+            patient_diagnosis: fields.text(17),
+            patient_birthdate: fields.value(18)?,
+            patient_sex: fields.value(19)?,
+            patient_race: fields.value(20)?,
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('S', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.text(&self.analytical_method);
+        dst.text(&self.instrumentation);
+        dst.text(&self.reagents);
+        dst.text(&self.units_of_measure);
+        dst.text(&self.quality_control);
+        dst.value(&self.specimen_descriptor);
+        dst.text(&self.reserved_field);
+        dst.text(&self.container);
+        dst.text(&self.specimen_id);
+        dst.text(&self.analyte);
+        dst.text(&self.result);
+        dst.text(&self.result_units);
+        dst.value(&self.collection_date_and_time);
+        dst.value(&self.result_date_and_time);
+        dst.text(&self.analytical_preprocessing_steps);
+        dst.text(&self.patient_diagnosis);
+        dst.value(&self.patient_birthdate);
+        dst.value(&self.patient_sex);
+        dst.value(&self.patient_race);
+        dst.finish(self.fields)
+    }
+}
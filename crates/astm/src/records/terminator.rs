@@ -0,0 +1,38 @@
+use crate::records::{Fields, Writer};
+use crate::values::TerminationCode;
+use crate::{Delimiters, Result};
+
+/// Message Terminator Record (L), the last record of every message.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageTerminatorRecord {
+    pub sequence_number: u32,
+    pub termination_code: Option<TerminationCode>,
+    /// Fields parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "crate::values::is_zero")
+    )]
+    pub fields: usize,
+}
+
+impl MessageTerminatorRecord {
+    pub fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Self::from_fields(&Fields::new(src, delimiters))
+    }
+
+    pub(crate) fn from_fields(fields: &Fields) -> Result<Self> {
+        Ok(Self {
+            sequence_number: fields.sequence_number(1)?,
+            termination_code: fields.value(2)?,
+            fields: fields.size(),
+        })
+    }
+
+    pub fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = Writer::new('L', delimiters);
+        dst.push(self.sequence_number.to_string());
+        dst.value(&self.termination_code);
+        dst.finish(self.fields)
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+
+use crate::records::{
+    CommentRecord, ManufacturerInformationRecord, MessageHeaderRecord, MessageTerminatorRecord,
+    OrderRecord, PatientRecord, Record, Records, RequestInformationRecord, ResultRecord,
+    ScientificRecord,
+};
+
+/// Record of the tree with its comments. The position is the index of
+/// the record in the message, the header being 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<T> {
+    pub position: usize,
+    pub record: T,
+    pub comments: Vec<Node<CommentRecord>>,
+}
+
+impl<T> Node<T> {
+    fn new(position: usize, record: T) -> Self {
+        Self {
+            position,
+            record,
+            comments: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatientNode {
+    pub position: usize,
+    pub record: PatientRecord,
+    pub comments: Vec<Node<CommentRecord>>,
+    pub orders: Vec<OrderNode>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderNode {
+    pub position: usize,
+    pub record: OrderRecord,
+    pub comments: Vec<Node<CommentRecord>>,
+    pub results: Vec<Node<ResultRecord>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    MissingHeader,
+    UnexpectedHeader,
+    MissingParent(char),
+    SequenceNumber {
+        record_type: char,
+        expected: u32,
+        found: u32,
+    },
+    MissingTerminator,
+    AfterTerminator(char),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "message does not start with a header record"),
+            Self::UnexpectedHeader => write!(f, "header record after the first record"),
+            Self::MissingParent(t) => write!(f, "{} record without a parent record", t),
+            Self::SequenceNumber {
+                record_type,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} record with sequence number {}, expected {}",
+                record_type, found, expected
+            ),
+            Self::MissingTerminator => write!(f, "message does not end with a terminator record"),
+            Self::AfterTerminator(t) => write!(f, "{} record after the terminator record", t),
+        }
+    }
+}
+
+/// Problem in the structure of a message, at the index of the record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructureProblem {
+    pub position: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for StructureProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Record {}: {}.", self.position, self.problem)
+    }
+}
+
+// Record that the next comments are attached to.
+#[derive(Clone, Copy)]
+enum Level {
+    None,
+    Header,
+    Patient,
+    Order,
+    Result,
+    Request,
+    Manufacturer,
+    Scientific,
+}
+
+/// Hierarchical view of the records of a message: patients with their
+/// orders and results, and comments attached to the record before them.
+///
+/// Records out of place, such as a result without an order, are left
+/// out of the tree and reported in `problems`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageTree {
+    pub header: Option<Node<MessageHeaderRecord>>,
+    pub patients: Vec<PatientNode>,
+    pub requests: Vec<Node<RequestInformationRecord>>,
+    pub manufacturer: Vec<Node<ManufacturerInformationRecord>>,
+    pub scientific: Vec<Node<ScientificRecord>>,
+    pub terminator: Option<Node<MessageTerminatorRecord>>,
+    problems: Vec<StructureProblem>,
+}
+
+impl MessageTree {
+    pub fn new(records: &Records) -> Self {
+        let mut dst = Self::default();
+        let mut level = Level::None;
+
+        if !matches!(records.iter().next(), Some(Record::MessageHeader(_))) {
+            dst.problem(0, Problem::MissingHeader);
+        }
+
+        for (position, record) in records.iter().enumerate() {
+            if dst.terminator.is_some() {
+                dst.problem(position, Problem::AfterTerminator(record.record_type()));
+                continue;
+            }
+
+            // comments of a record out of place are out of place too
+            level = match dst.push(position, record, level) {
+                Some(t) => t,
+                None => {
+                    let problem = Problem::MissingParent(record.record_type());
+                    dst.problem(position, problem);
+                    Level::None
+                }
+            };
+        }
+
+        if dst.terminator.is_none() {
+            dst.problem(records.len(), Problem::MissingTerminator);
+        }
+
+        dst
+    }
+
+    /// Problems found in the structure, in order of position.
+    pub fn problems(&self) -> &[StructureProblem] {
+        &self.problems
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn problem(&mut self, position: usize, problem: Problem) {
+        self.problems.push(StructureProblem { position, problem });
+    }
+
+    // Checks that a record follows the last one of its level.
+    fn sequence(&mut self, position: usize, record: &Record, expected: usize, found: u32) {
+        let expected = expected as u32 + 1;
+
+        if found != expected {
+            let problem = Problem::SequenceNumber {
+                record_type: record.record_type(),
+                expected,
+                found,
+            };
+            self.problem(position, problem);
+        }
+    }
+
+    // Adds a record to the tree, returning the level of the comments
+    // after it, or `None` when it has no parent.
+    fn push(&mut self, position: usize, record: &Record, level: Level) -> Option<Level> {
+        match record {
+            Record::MessageHeader(t) => {
+                if position != 0 {
+                    self.problem(position, Problem::UnexpectedHeader);
+                    return Some(level);
+                }
+
+                self.header = Some(Node::new(position, t.clone()));
+                Some(Level::Header)
+            }
+            Record::Patient(t) => {
+                self.sequence(position, record, self.patients.len(), t.sequence_number);
+                self.patients.push(PatientNode {
+                    position,
+                    record: t.clone(),
+                    comments: vec![],
+                    orders: vec![],
+                });
+                Some(Level::Patient)
+            }
+            Record::Order(t) => {
+                let expected = self.patients.last()?.orders.len();
+                self.sequence(position, record, expected, t.sequence_number);
+                self.patients.last_mut()?.orders.push(OrderNode {
+                    position,
+                    record: t.clone(),
+                    comments: vec![],
+                    results: vec![],
+                });
+                Some(Level::Order)
+            }
+            Record::Result(t) => {
+                let expected = self.patients.last()?.orders.last()?.results.len();
+                self.sequence(position, record, expected, t.sequence_number);
+                let order = self.patients.last_mut()?.orders.last_mut()?;
+                order.results.push(Node::new(position, t.clone()));
+                Some(Level::Result)
+            }
+            Record::Comment(t) => {
+                let expected = self.comments(level)?.len();
+                self.sequence(position, record, expected, t.sequence_number);
+                self.comments(level)?.push(Node::new(position, t.clone()));
+                Some(level)
+            }
+            Record::RequestInformation(t) => {
+                self.sequence(position, record, self.requests.len(), t.sequence_number);
+                self.requests.push(Node::new(position, t.clone()));
+                Some(Level::Request)
+            }
+            Record::MessageTerminator(t) => {
+                self.sequence(position, record, 0, t.sequence_number);
+                self.terminator = Some(Node::new(position, t.clone()));
+                Some(Level::None)
+            }
+            Record::ManufacturerInformation(t) => {
+                let expected = self.manufacturer.len();
+                self.sequence(position, record, expected, t.sequence_number);
+                self.manufacturer.push(Node::new(position, t.clone()));
+                Some(Level::Manufacturer)
+            }
+            Record::Scientific(t) => {
+                let expected = self.scientific.len();
+                self.sequence(position, record, expected, t.sequence_number);
+                self.scientific.push(Node::new(position, t.clone()));
+                Some(Level::Scientific)
+            }
+        }
+    }
+
+    fn comments(&mut self, level: Level) -> Option<&mut Vec<Node<CommentRecord>>> {
+        let dst = match level {
+            Level::None => return None,
+            Level::Header => &mut self.header.as_mut()?.comments,
+            Level::Patient => &mut self.patients.last_mut()?.comments,
+            Level::Order => &mut self.patients.last_mut()?.orders.last_mut()?.comments,
+            Level::Result => {
+                let order = self.patients.last_mut()?.orders.last_mut()?;
+                &mut order.results.last_mut()?.comments
+            }
+            Level::Request => &mut self.requests.last_mut()?.comments,
+            Level::Manufacturer => &mut self.manufacturer.last_mut()?.comments,
+            Level::Scientific => &mut self.scientific.last_mut()?.comments,
+        };
+
+        Some(dst)
+    }
+}
+
+impl From<&Records> for MessageTree {
+    fn from(src: &Records) -> Self {
+        Self::new(src)
+    }
+}
@@ -0,0 +1,261 @@
+use chrono::{DateTime, Utc};
+use log::debug;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{sleep_until, Duration, Instant};
+
+use crate::driver::process;
+use crate::link::{DataLink, Input, ManualClock};
+use crate::trace::{Direction, Entry};
+use crate::{pcap, ASTMError, Action, Result, ASTM};
+
+/// Recorded session, from a trace file or a pcap capture.
+///
+/// Directions are seen from our side of the link: `in` bytes were sent
+/// by the instrument, `out` bytes by us.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    entries: Vec<Entry>,
+}
+
+impl Recording {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        Self { entries }
+    }
+
+    pub async fn open_trace<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let src = fs::read_to_string(path)
+            .await
+            .map_err(|t| ASTMError::ReadRecording(t.to_string()))?;
+        Self::from_trace(&src)
+    }
+
+    /// Reads the TCP connections to `port`, the one we were listening on.
+    pub async fn open_pcap<P: AsRef<Path>>(path: P, port: u16) -> Result<Self> {
+        let src = fs::read(path)
+            .await
+            .map_err(|t| ASTMError::ReadRecording(t.to_string()))?;
+        Self::from_pcap(&src, port)
+    }
+
+    pub fn from_trace(src: &str) -> Result<Self> {
+        let entries = src
+            .lines()
+            .enumerate()
+            .filter(|(_, t)| !t.is_empty())
+            .map(|(index, t)| {
+                t.parse()
+                    .map_err(|t: ASTMError| ASTMError::InvalidTraceLine(index + 1, t.to_string()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { entries })
+    }
+
+    pub fn from_pcap(src: &[u8], port: u16) -> Result<Self> {
+        Ok(Self {
+            entries: pcap::parse(src, port)?,
+        })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Connection ids, in order of appearance.
+    pub fn connections(&self) -> Vec<u64> {
+        let mut dst: Vec<u64> = vec![];
+
+        for entry in &self.entries {
+            if !dst.contains(&entry.connection) {
+                dst.push(entry.connection);
+            }
+        }
+
+        dst
+    }
+
+    /// Recording of a single connection.
+    pub fn connection(&self, id: u64) -> Self {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|t| t.connection == id)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Plays the instrument side against a live server, writing the `in`
+    /// bytes of every connection in turn. A `speed` of 1.0 keeps the
+    /// original timing, 2.0 plays twice as fast and `f64::INFINITY`
+    /// does not wait at all. The bytes do not wait for the server
+    /// replies, like the original instrument did not.
+    ///
+    /// Returns what the server sent back, as `out` entries.
+    pub async fn play(&self, address: SocketAddr, speed: f64) -> Result<Vec<Entry>> {
+        let mut dst = vec![];
+
+        for id in self.connections() {
+            let recording = self.connection(id);
+            let start = recording.entries[0].timestamp;
+
+            let stream = TcpStream::connect(address)
+                .await
+                .map_err(|t| ASTMError::Connection(t.to_string()))?;
+            let (mut read, mut write) = stream.into_split();
+            debug!("Playing connection {} against {}.", id, address);
+
+            let reader = tokio::spawn(async move {
+                let mut dst = vec![];
+                let mut buffer = [0_u8; 4096];
+
+                while let Ok(size @ 1..) = read.read(&mut buffer).await {
+                    dst.push(Entry {
+                        timestamp: Utc::now(),
+                        connection: id,
+                        direction: Direction::Out,
+                        data: buffer[0..size].to_vec(),
+                    });
+                }
+
+                dst
+            });
+
+            let begin = Instant::now();
+
+            for entry in recording.entries.iter() {
+                if entry.direction != Direction::In {
+                    continue;
+                }
+
+                let offset = elapsed(&start, &entry.timestamp).as_secs_f64() / speed;
+                sleep_until(begin + Duration::try_from_secs_f64(offset).unwrap_or_default()).await;
+
+                write
+                    .write_all(&entry.data)
+                    .await
+                    .map_err(|t| ASTMError::Connection(t.to_string()))?;
+            }
+
+            // wait for the tail of the session before closing
+            if let Some(last) = recording.entries.last() {
+                let offset = elapsed(&start, &last.timestamp).as_secs_f64() / speed;
+                sleep_until(begin + Duration::try_from_secs_f64(offset).unwrap_or_default()).await;
+            }
+
+            write
+                .shutdown()
+                .await
+                .map_err(|t| ASTMError::Connection(t.to_string()))?;
+            dst.extend(
+                reader
+                    .await
+                    .map_err(|t| ASTMError::Connection(t.to_string()))?,
+            );
+        }
+
+        Ok(dst)
+    }
+}
+
+fn elapsed(start: &DateTime<Utc>, src: &DateTime<Utc>) -> Duration {
+    (*src - *start).to_std().unwrap_or_default()
+}
+
+/// Feeds the `in` bytes of every connection through a new data link and
+/// the instrument actions. Time is simulated, timers fire at the recorded
+/// time they would have, up to the last entry of the connection.
+///
+/// Returns what the link sent back, as `out` entries.
+pub(crate) async fn replay<S>(recording: &Recording, astm: &ASTM<S>) -> Result<Vec<Entry>>
+where
+    S: Clone + Sync + Action<S>,
+{
+    let mut dst = vec![];
+
+    for id in recording.connections() {
+        let recording = recording.connection(id);
+        let start = recording.entries[0].timestamp;
+        let end = recording
+            .entries
+            .last()
+            .map(|t| elapsed(&start, &t.timestamp));
+
+        let clock = ManualClock::default();
+        let mut session = Session {
+            data_link: DataLink::new(astm.settings.clone(), Arc::new(clock.clone())),
+            clock,
+            start,
+            connection: id,
+            astm,
+            dst: &mut dst,
+        };
+
+        for entry in recording.entries.iter() {
+            if entry.direction != Direction::In {
+                continue;
+            }
+
+            let at = elapsed(&start, &entry.timestamp).as_micros() as u64;
+            session.expire(at).await?;
+            session.handle(at, Input::Bytes(entry.data.clone())).await?;
+        }
+
+        if let Some(end) = end {
+            session.expire(end.as_micros() as u64).await?;
+        }
+    }
+
+    Ok(dst)
+}
+
+struct Session<'a, S: Clone> {
+    data_link: DataLink,
+    clock: ManualClock,
+    start: DateTime<Utc>,
+    connection: u64,
+    astm: &'a ASTM<S>,
+    dst: &'a mut Vec<Entry>,
+}
+
+impl<'a, S> Session<'a, S>
+where
+    S: Clone + Sync + Action<S>,
+{
+    async fn handle(&mut self, at: u64, input: Input) -> Result<()> {
+        self.clock.set(at);
+
+        let mut sent: Vec<u8> = vec![];
+        process(&mut self.data_link, input, self.astm, &None, &mut sent).await?;
+
+        if !sent.is_empty() {
+            self.dst.push(Entry {
+                timestamp: self.start + chrono::Duration::microseconds(at as i64),
+                connection: self.connection,
+                direction: Direction::Out,
+                data: sent,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Fires every timer due up to `until`.
+    async fn expire(&mut self, until: u64) -> Result<()> {
+        while let Some((timer, at)) = self.data_link.next_timer() {
+            if at > until {
+                break;
+            }
+
+            self.handle(at, Input::Timer(timer)).await?;
+        }
+
+        Ok(())
+    }
+}
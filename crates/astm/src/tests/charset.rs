@@ -0,0 +1,70 @@
+use crate::{ASTMError, CharEncoding, Frame};
+
+#[test]
+fn from_label() {
+    let names = [
+        ("ascii", "ascii"),
+        ("ISO-8859-1", "iso-8859-1"),
+        ("windows-1252", "windows-1252"),
+        ("latin1", "windows-1252"),
+        ("Shift_JIS", "windows-31j"),
+        (" gb18030 ", "gb18030"),
+        ("utf8", "utf-8"),
+    ];
+
+    for (label, name) in names {
+        assert_eq!(CharEncoding::from_label(label).unwrap().name(), name);
+    }
+
+    assert_eq!(
+        CharEncoding::from_label("ebcdic"),
+        Err(ASTMError::UnknownEncoding("ebcdic".to_string()))
+    );
+    assert!(CharEncoding::from_label("error").is_err());
+}
+
+#[test]
+fn round_trip() {
+    let texts = [
+        (CharEncoding::ISO_8859_1, "P|1||||Müller^Zoë"),
+        (CharEncoding::WINDOWS_1252, "P|1||||Dupré^€"),
+        (CharEncoding::SHIFT_JIS, "P|1||||山田^太郎"),
+        (CharEncoding::GB18030, "P|1||||王^小明"),
+        (CharEncoding::WINDOWS_1251, "P|1||||Иванов"),
+    ];
+
+    for (encoding, text) in texts {
+        let encoded = encoding.encode(text).unwrap();
+        assert_ne!(encoded, text.as_bytes());
+        assert_eq!(encoding.decode(&encoded).unwrap(), text);
+
+        let frame = Frame {
+            number: 1,
+            data: format!("{}\r", text),
+            last: true,
+        };
+        let raw = frame.serialize(encoding.clone()).unwrap();
+        let (dst, _) = Frame::deserialize(&raw, encoding, &Default::default()).unwrap();
+        assert_eq!(dst, frame);
+    }
+}
+
+#[test]
+fn unmappable_characters() {
+    let error = CharEncoding::ASCII.encode("Müller").unwrap_err();
+    assert!(matches!(error, ASTMError::Charset("ascii", _)));
+
+    let error = CharEncoding::UTF8.decode(b"M\xFCller").unwrap_err();
+    assert!(matches!(error, ASTMError::Charset("utf-8", _)));
+}
+
+#[test]
+fn replacement() {
+    let ascii = CharEncoding::ASCII.with_replacement();
+    assert_eq!(ascii.encode("Müller").unwrap(), b"M?ller");
+    assert_eq!(ascii.decode(b"M\xFCller").unwrap(), "M\u{FFFD}ller");
+
+    let utf8 = CharEncoding::UTF8.with_replacement();
+    assert_eq!(utf8.decode(b"M\xFCller").unwrap(), "M\u{FFFD}ller");
+    assert_ne!(utf8, CharEncoding::UTF8);
+}
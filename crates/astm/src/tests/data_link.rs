@@ -0,0 +1,438 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+use crate::{
+    Action, Clock, DataLink, Deviation, Frame, Input, LinkMode, ManualClock, Message, Output,
+    Priority, Quirks, Role, Settings, State, Timer, ASTM,
+};
+
+const ENQ: u8 = 0x05;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const EOT: u8 = 0x04;
+
+fn build_data_link(settings: Settings) -> (DataLink, ManualClock) {
+    let clock = ManualClock::default();
+    (DataLink::new(settings, Arc::new(clock.clone())), clock)
+}
+
+fn frame(number: u8, data: &str) -> Vec<u8> {
+    Frame {
+        number,
+        data: data.to_string(),
+        last: true,
+    }
+    .serialize(crate::CharEncoding::ASCII)
+    .unwrap()
+}
+
+// Handles an input accepting every frame, like the default `Action`.
+fn handle(data_link: &mut DataLink, input: Input) -> Vec<Output> {
+    let mut dst = vec![];
+    let mut inputs = vec![input];
+
+    while let Some(input) = inputs.pop() {
+        for output in data_link.handle(input) {
+            match output {
+                Output::CheckFrame(frame, _) => inputs.push(Input::FrameChecked(Ok(frame))),
+                t => dst.push(t),
+            }
+        }
+    }
+
+    dst
+}
+
+fn sent(outputs: &[Output]) -> Vec<Vec<u8>> {
+    outputs
+        .iter()
+        .filter_map(|t| match t {
+            Output::Send(t) => Some(t.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn recv(data_link: &mut DataLink, src: &[u8]) -> Vec<Vec<u8>> {
+    sent(&handle(data_link, Input::Bytes(src.to_vec())))
+}
+
+// Moves the clock to the next timer and fires it.
+fn expire(data_link: &mut DataLink, clock: &ManualClock) -> Vec<Output> {
+    let (timer, at) = data_link.next_timer().unwrap();
+    clock.set(at);
+    handle(data_link, Input::Timer(timer))
+}
+
+fn queue(data_link: &mut DataLink, src: &str) -> Vec<Vec<u8>> {
+    let message: Message = src.parse().unwrap();
+    sent(&handle(data_link, Input::Queue(message)))
+}
+
+#[test]
+fn resend_frame_on_nak() {
+    let (mut data_link, _) = build_data_link(Settings::default());
+
+    assert_eq!(queue(&mut data_link, "H|\\^&\r"), vec![vec![ENQ]]);
+
+    let first = recv(&mut data_link, &[ACK]);
+    assert_eq!(first, vec![frame(1, "H|\\^&\r")]);
+    assert_eq!(recv(&mut data_link, &[NAK]), first);
+
+    let outputs = handle(&mut data_link, Input::Bytes(vec![ACK]));
+    assert_eq!(sent(&outputs), vec![vec![EOT]]);
+    assert!(!outputs.iter().any(|t| matches!(t, Output::Abort(_))));
+    assert_eq!(data_link.state(), &State::Idle);
+}
+
+#[test]
+fn abort_after_max_retries() {
+    let (mut data_link, _) = build_data_link(Settings::default());
+    let message: Message = "H|\\^&\r".parse().unwrap();
+
+    handle(&mut data_link, Input::Queue(message.clone()));
+    recv(&mut data_link, &[ACK]);
+
+    for _ in 1..6 {
+        assert_eq!(recv(&mut data_link, &[NAK]), vec![frame(1, "H|\\^&\r")]);
+    }
+
+    let outputs = handle(&mut data_link, Input::Bytes(vec![NAK]));
+    assert_eq!(sent(&outputs), vec![vec![EOT]]);
+    assert!(outputs.contains(&Output::Abort(message)));
+    assert_eq!(data_link.state(), &State::Idle);
+}
+
+#[test]
+fn host_yields_on_contention() {
+    let (mut data_link, clock) = build_data_link(Settings::default());
+
+    assert_eq!(queue(&mut data_link, "H|\\^&\r"), vec![vec![ENQ]]);
+
+    assert!(recv(&mut data_link, &[ENQ]).is_empty());
+    assert_eq!(data_link.state(), &State::Contention);
+    assert_eq!(data_link.queued(), 1);
+
+    assert_eq!(recv(&mut data_link, &[ENQ]), vec![vec![ACK]]);
+    assert_eq!(data_link.state(), &State::Receiving);
+
+    assert!(recv(&mut data_link, &[EOT]).is_empty());
+    assert_eq!(data_link.state(), &State::Contention);
+
+    assert_eq!(sent(&expire(&mut data_link, &clock)), vec![vec![ENQ]]);
+    assert_eq!(clock.now(), 20_000_000);
+    assert_eq!(data_link.state(), &State::Sending);
+}
+
+#[test]
+fn instrument_waits_on_contention() {
+    let settings = Settings {
+        role: Role::Instrument,
+        ..Default::default()
+    };
+    let (mut data_link, clock) = build_data_link(settings);
+
+    assert_eq!(queue(&mut data_link, "H|\\^&\r"), vec![vec![ENQ]]);
+    assert!(recv(&mut data_link, &[ENQ]).is_empty());
+    assert_eq!(data_link.state(), &State::Contention);
+
+    assert_eq!(sent(&expire(&mut data_link, &clock)), vec![vec![ENQ]]);
+    assert_eq!(clock.now(), 1_000_000);
+    assert_eq!(data_link.state(), &State::Sending);
+}
+
+#[test]
+fn receive_duplicate_and_out_of_sequence_frames() {
+    let (mut data_link, _) = build_data_link(Settings::default());
+
+    assert_eq!(recv(&mut data_link, &[ENQ]), vec![vec![ACK]]);
+    assert_eq!(recv(&mut data_link, &frame(1, "H|\\^&\r")), vec![vec![ACK]]);
+    assert_eq!(recv(&mut data_link, &frame(1, "H|\\^&\r")), vec![vec![ACK]]);
+    assert_eq!(recv(&mut data_link, &frame(3, "L|1|N\r")), vec![vec![NAK]]);
+
+    let outputs = handle(&mut data_link, Input::Bytes(vec![EOT]));
+    let message: Message = "H|\\^&\r".parse().unwrap();
+    assert!(outputs.contains(&Output::Deliver(message)));
+}
+
+#[test]
+fn count_accepted_deviations() {
+    let settings = ASTM::new(())
+        .quirks(Quirks {
+            lowercase_checksum: true,
+            missing_lf: true,
+            ..Default::default()
+        })
+        .settings()
+        .clone();
+    let (mut data_link, _) = build_data_link(settings);
+
+    let mut first = frame(1, "H|\\^&\r");
+    first.pop();
+    let mut second = frame(2, "L|1|N\r");
+    second.pop();
+
+    assert_eq!(recv(&mut data_link, &[ENQ]), vec![vec![ACK]]);
+    assert_eq!(recv(&mut data_link, &first), vec![vec![ACK]]);
+    assert_eq!(recv(&mut data_link, &second), vec![vec![ACK]]);
+
+    // <STX>3H|\^&<CR><ETX>e7<CR><LF>
+    let mut third = frame(3, "H|\\^&\r");
+    let size = third.len();
+    third[size - 4] = b'e';
+    assert_eq!(recv(&mut data_link, &third), vec![vec![ACK]]);
+
+    let deviations = data_link.deviations();
+    assert_eq!(deviations.count(Deviation::MissingLF), 2);
+    assert_eq!(deviations.count(Deviation::LowercaseChecksum), 1);
+    assert_eq!(deviations.total(), 3);
+}
+
+#[test]
+fn send_queued_messages_by_priority() {
+    let (mut data_link, _) = build_data_link(Settings::default());
+
+    assert_eq!(queue(&mut data_link, "H|\\^&\rL|1|N\r"), vec![vec![ENQ]]);
+    assert!(queue(&mut data_link, "H|\\^&\rL|1|N\r").is_empty());
+
+    let stat: Message = "H|\\^&\rP|1\rL|1|N\r".parse().unwrap();
+    handle(
+        &mut data_link,
+        Input::Queue(stat.with_priority(Priority::Stat)),
+    );
+    assert_eq!(data_link.queued(), 2);
+
+    assert_eq!(recv(&mut data_link, &[ACK]), vec![frame(1, "H|\\^&\r")]);
+    assert_eq!(recv(&mut data_link, &[ACK]), vec![frame(2, "L|1|N\r")]);
+
+    // the stat message goes out right after the first one.
+    assert_eq!(recv(&mut data_link, &[ACK]), vec![vec![EOT], vec![ENQ]]);
+    assert_eq!(recv(&mut data_link, &[ACK]), vec![frame(1, "H|\\^&\r")]);
+    assert_eq!(recv(&mut data_link, &[ACK]), vec![frame(2, "P|1\r")]);
+    assert_eq!(recv(&mut data_link, &[ACK]), vec![frame(3, "L|1|N\r")]);
+    assert_eq!(data_link.queued(), 1);
+}
+
+#[test]
+fn receive_timeout() {
+    let (mut data_link, clock) = build_data_link(Settings::default());
+
+    assert_eq!(recv(&mut data_link, &[ENQ]), vec![vec![ACK]]);
+    assert_eq!(data_link.next_timer(), Some((Timer::Receive, 30_000_000)));
+
+    // a stale timer does nothing.
+    assert!(handle(&mut data_link, Input::Timer(Timer::Receive)).is_empty());
+
+    assert_eq!(sent(&expire(&mut data_link, &clock)), vec![vec![NAK]]);
+    assert_eq!(data_link.state(), &State::Idle);
+}
+
+#[test]
+fn reply_timeout_and_busy_delay() {
+    let settings = Settings {
+        reply_timeout: Duration::from_micros(1500),
+        busy_delay: Duration::from_micros(250),
+        ..Default::default()
+    };
+    let (mut data_link, clock) = build_data_link(settings);
+
+    assert_eq!(queue(&mut data_link, "H|\\^&\r"), vec![vec![ENQ]]);
+
+    // no reply, <ENQ> is sent again after the reply timeout.
+    assert_eq!(sent(&expire(&mut data_link, &clock)), vec![vec![ENQ]]);
+    assert_eq!(clock.now(), 1500);
+
+    // receiver busy, <ENQ> is sent again after the busy delay.
+    assert!(recv(&mut data_link, &[NAK]).is_empty());
+    assert_eq!(data_link.state(), &State::Idle);
+    assert_eq!(sent(&expire(&mut data_link, &clock)), vec![vec![ENQ]]);
+    assert_eq!(clock.now(), 1750);
+}
+
+#[test]
+fn session_with_lost_ack() {
+    let clock = ManualClock::default();
+    let settings = Settings {
+        role: Role::Instrument,
+        frame_size: 16,
+        ..Default::default()
+    };
+    let mut instrument = DataLink::new(settings, Arc::new(clock.clone()));
+    let mut host = DataLink::new(Settings::default(), Arc::new(clock.clone()));
+
+    let src = "H|\\^&|||Analyzer^1.0\rR|1|^^^GLU|98|mg/dL||N||F\rL|1|N\r";
+    let message: Message = src.parse().unwrap();
+
+    let mut to_host = sent(&handle(&mut instrument, Input::Queue(message.clone())));
+    let mut delivered = vec![];
+    let mut acks = 0;
+
+    loop {
+        let mut to_instrument = vec![];
+
+        for chunk in to_host.drain(..) {
+            for output in handle(&mut host, Input::Bytes(chunk)) {
+                match output {
+                    Output::Send(t) => to_instrument.push(t),
+                    Output::Deliver(t) => delivered.push(t),
+                    _ => {}
+                }
+            }
+        }
+
+        // the third <ACK> never reaches the instrument.
+        to_instrument.retain(|t| {
+            acks += (t == &vec![ACK]) as usize;
+            acks != 3 || t != &vec![ACK]
+        });
+
+        for chunk in to_instrument {
+            to_host.extend(recv(&mut instrument, &chunk));
+        }
+
+        if to_host.is_empty() {
+            match instrument.next_timer() {
+                Some(_) => to_host.extend(sent(&expire(&mut instrument, &clock))),
+                None => break,
+            }
+        }
+    }
+
+    assert_eq!(clock.now(), 15_000_000);
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].records(), message.records());
+    assert_eq!(instrument.state(), &State::Idle);
+    assert_eq!(host.state(), &State::Idle);
+}
+
+fn raw_data_link() -> (DataLink, ManualClock) {
+    build_data_link(Settings {
+        mode: LinkMode::Raw,
+        ..Default::default()
+    })
+}
+
+fn delivered(outputs: &[Output]) -> Vec<Vec<String>> {
+    outputs
+        .iter()
+        .filter_map(|t| match t {
+            Output::Deliver(t) => Some(t.records()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn raw_records_end_with_l_record() {
+    let (mut data_link, _) = raw_data_link();
+
+    let outputs = handle(
+        &mut data_link,
+        Input::Bytes(b"H|\\^&\r\nP|1\r\nR|1|^^^GLU".to_vec()),
+    );
+    assert!(sent(&outputs).is_empty());
+    assert!(delivered(&outputs).is_empty());
+    assert_eq!(data_link.state(), &State::Receiving);
+
+    let outputs = handle(&mut data_link, Input::Bytes(b"|98\r\nL|1|N\r\n".to_vec()));
+    assert!(sent(&outputs).is_empty());
+    assert_eq!(
+        delivered(&outputs),
+        vec![vec!["H|\\^&", "P|1", "R|1|^^^GLU|98", "L|1|N"]]
+    );
+    assert_eq!(data_link.state(), &State::Idle);
+}
+
+#[test]
+fn raw_records_end_with_eot() {
+    let (mut data_link, _) = raw_data_link();
+
+    // a record that is not an `L` one, other delimiters
+    let outputs = handle(
+        &mut data_link,
+        Input::Bytes(b"H!\\^&\rLOT!1\rR!1\x04".to_vec()),
+    );
+    assert_eq!(delivered(&outputs), vec![vec!["H!\\^&", "LOT!1", "R!1"]]);
+
+    // <EOT> alone does not deliver empty messages
+    assert!(delivered(&handle(&mut data_link, Input::Bytes(vec![EOT]))).is_empty());
+}
+
+#[test]
+fn raw_receive_timeout() {
+    let (mut data_link, clock) = raw_data_link();
+
+    handle(&mut data_link, Input::Bytes(b"H|\\^&\r".to_vec()));
+    let outputs = expire(&mut data_link, &clock);
+    assert!(sent(&outputs).is_empty());
+    assert_eq!(data_link.state(), &State::Idle);
+
+    let outputs = handle(&mut data_link, Input::Bytes(b"H|\\^&\rL|1\r".to_vec()));
+    assert_eq!(delivered(&outputs), vec![vec!["H|\\^&", "L|1"]]);
+}
+
+#[test]
+fn raw_send_records() {
+    let (mut data_link, _) = raw_data_link();
+
+    assert_eq!(
+        queue(&mut data_link, "H|\\^&\rL|1|N\r"),
+        vec![b"H|\\^&\rL|1|N\r".to_vec()]
+    );
+    assert_eq!(data_link.state(), &State::Idle);
+    assert_eq!(data_link.queued(), 0);
+}
+
+#[derive(Clone, Default)]
+struct Instrument {
+    received: Arc<Mutex<Vec<Message>>>,
+    states: Arc<Mutex<Vec<State>>>,
+}
+
+#[async_trait]
+impl Action<Instrument> for Instrument {
+    async fn on_recv_message(&self, message: &Message) -> Option<Message> {
+        self.received.lock().await.push(message.clone());
+        "H|\\^&\rL|1|N\r".parse().ok()
+    }
+
+    async fn on_state_change(&self, state: &State) {
+        self.states.lock().await.push(state.clone());
+    }
+}
+
+#[tokio::test]
+async fn serve_over_stream() {
+    let instrument = Instrument::default();
+    let (local, mut remote) = tokio::io::duplex(1024);
+    let (read, write) = tokio::io::split(local);
+
+    let astm = ASTM::new(instrument.clone());
+    let task = tokio::spawn(async move { astm.serve(read, write).await });
+
+    let mut buffer = [0u8; 64];
+
+    // <ENQ> and the frame in the same write
+    let mut src = vec![ENQ];
+    src.extend(frame(1, "H|\\^&\r"));
+    remote.write_all(&src).await.unwrap();
+    remote.read_exact(&mut buffer[..2]).await.unwrap();
+    assert_eq!(&buffer[..2], &[ACK, ACK]);
+
+    remote.write_all(&[EOT]).await.unwrap();
+    remote.read_exact(&mut buffer[..1]).await.unwrap();
+    assert_eq!(buffer[0], ENQ);
+
+    drop(remote);
+    task.await.unwrap().unwrap();
+
+    let message: Message = "H|\\^&\r".parse().unwrap();
+    assert_eq!(*instrument.received.lock().await, vec![message]);
+    assert_eq!(
+        *instrument.states.lock().await,
+        vec![State::Receiving, State::Idle, State::Sending]
+    );
+}
@@ -0,0 +1,116 @@
+use crate::values::*;
+use crate::{ASTMError, Delimiters, Message};
+
+#[test]
+fn escape_delimiters() {
+    let delimiters = Delimiters::default();
+    assert_eq!(delimiters.escape("A|B\\C^D&E"), "A&F&B&R&C&S&D&E&E");
+    assert_eq!(delimiters.escape("line 1\rline 2"), "line 1&X0D&line 2");
+}
+
+#[test]
+fn unescape_delimiters() {
+    let delimiters = Delimiters::default();
+    assert_eq!(delimiters.unescape("A&F&B&R&C&S&D&E&E"), "A|B\\C^D&E");
+    assert_eq!(delimiters.unescape("&X0D0A&"), "\r\n");
+    assert_eq!(delimiters.unescape("&XC3A9&"), "é");
+    assert_eq!(delimiters.unescape("&XE9&"), "é");
+}
+
+#[test]
+fn unescape_unknown_sequences() {
+    let delimiters = Delimiters::default();
+    assert_eq!(delimiters.unescape("&H&bold&N&"), "&H&bold&N&");
+    assert_eq!(delimiters.unescape("Smith & Jones"), "Smith & Jones");
+    assert_eq!(delimiters.unescape("&X0&&X+F&"), "&X0&&X+F&");
+    assert_eq!(delimiters.unescape("A & B&S&C"), "A & B^C");
+}
+
+#[test]
+fn escape_round_trip() {
+    let delimiters = Delimiters {
+        field: '!',
+        repeat: '@',
+        component: '#',
+        escape: '$',
+    };
+    let src = "100$ | 50% ^ 2#3 !@";
+    let escaped = delimiters.escape(src);
+    assert_eq!(escaped, "100$E$ | 50% ^ 2$S$3 $F$$R$");
+    assert_eq!(delimiters.unescape(&escaped), src);
+}
+
+#[test]
+fn delimiters_from_header() {
+    assert_eq!(
+        Delimiters::from_header("H|\\^&|||Host").unwrap(),
+        Delimiters::default()
+    );
+    assert_eq!(
+        Delimiters::from_header("H|\\^&").unwrap(),
+        Delimiters::default()
+    );
+    assert_eq!(
+        Delimiters::from_header("H!~#$!!!Host").unwrap(),
+        Delimiters {
+            field: '!',
+            repeat: '~',
+            component: '#',
+            escape: '$',
+        }
+    );
+}
+
+#[test]
+fn invalid_delimiters() {
+    assert_eq!(
+        Delimiters::from_header("P|1"),
+        Err(ASTMError::MissingHeaderRecord)
+    );
+    assert_eq!(
+        Delimiters::from_header("H|\\^"),
+        Err(ASTMError::InvalidDelimiters("H|\\^".to_string()))
+    );
+    assert_eq!(
+        Delimiters::from_header("H|\\^|||"),
+        Err(ASTMError::InvalidDelimiters("H|\\^||".to_string()))
+    );
+    assert_eq!(
+        Delimiters::from_header("H|\\^&X|"),
+        Err(ASTMError::InvalidDelimiters("H|\\^&X".to_string()))
+    );
+    assert_eq!(
+        Delimiters::from_header("H|A^&|"),
+        Err(ASTMError::InvalidDelimiters("H|A^&|".to_string()))
+    );
+}
+
+#[test]
+fn message_with_custom_delimiters() {
+    let message: Message = "H!~#$!!!Host\rP!1!!!!DOE$S$SMITH#JOHN\rL!1\r"
+        .parse()
+        .unwrap();
+    let delimiters = message.delimiters().unwrap();
+    assert_eq!(delimiters.field, '!');
+
+    let records = message.records();
+    let field = records[1].split(delimiters.field).nth(5).unwrap();
+    let name = PatientName::parse(field, &delimiters).unwrap();
+    assert_eq!(name.last_name, Some("DOE#SMITH".to_string()));
+    assert_eq!(name.first_name, Some("JOHN".to_string()));
+    assert_eq!(name.serialize(&delimiters), field);
+}
+
+#[test]
+fn single_values_with_delimiters() {
+    let delimiters = Delimiters::default();
+    let race = PatientRace::parse("MIXED&S&OTHER", &delimiters).unwrap();
+    assert_eq!(race, PatientRace::Other("MIXED^OTHER".to_string()));
+    assert_eq!(race.serialize(&delimiters), "MIXED&S&OTHER");
+
+    let date_time = ASTMDateTime::parse("20190821102030-0300", &delimiters).unwrap();
+    assert_eq!(date_time.serialize(&delimiters), "20190821102030-0300");
+    let date = ASTMDate::parse("20190821", &delimiters).unwrap();
+    assert_eq!(date.serialize(&delimiters), "20190821");
+    assert_eq!(ProcessingID::QualityControl.serialize(&delimiters), "Q");
+}
@@ -0,0 +1,113 @@
+use serde_json::json;
+
+use crate::records::*;
+use crate::{Message, Records};
+
+#[test]
+fn values_to_json() {
+    let flags = Repeated::<AbnormalFlag>::parse("H\\\\W", &Default::default()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&flags).unwrap(),
+        json!(["H", null, "W"])
+    );
+
+    let date_time: ASTMDateTime = "20190821102030-0300".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(&date_time).unwrap(),
+        json!("20190821102030-0300")
+    );
+
+    let name: PatientName = "DOE^JOHN".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(&name).unwrap(),
+        json!({
+            "last_name": "DOE",
+            "first_name": "JOHN",
+            "middle_name": null,
+            "suffix": null,
+            "title": null
+        })
+    );
+
+    let height: Measurement = "1.8^m".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(&height).unwrap(),
+        json!({ "measure": 1.8, "unit": "m" })
+    );
+
+    let height: Measurement = "170.0^cm^".parse().unwrap();
+    let value = serde_json::to_value(&height).unwrap();
+    assert_eq!(
+        value,
+        json!({ "measure": 170.0, "unit": "cm", "text": "170.0", "components": 3 })
+    );
+    let dst: Measurement = serde_json::from_value(value).unwrap();
+    assert_eq!(dst.to_string(), "170.0^cm^");
+}
+
+#[test]
+fn values_from_json() {
+    let flags: Repeated<AbnormalFlag> = serde_json::from_value(json!(["HH", null])).unwrap();
+    assert_eq!(flags.repeats(), &[Some(AbnormalFlag::AbovePanicHigh), None]);
+
+    let status: ResultStatus = serde_json::from_value(json!("F")).unwrap();
+    assert_eq!(status, ResultStatus::Final);
+
+    let date: ASTMDate = serde_json::from_value(json!("20190821")).unwrap();
+    assert_eq!(date, "20190821".parse().unwrap());
+
+    let error = serde_json::from_value::<PatientSex>(json!("Z")).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid Patient Sex value.");
+}
+
+#[test]
+fn records_json_round_trip() {
+    let src = concat!(
+        "H|\\^&|||Host|||||||P|LIS2-A2|20190821102030-0300\r",
+        "P|1||PID&F&1||DOE^JOHN||19800101|M||||||||170.0^cm|1e2^kg|\r",
+        "O|1|SID||^^^248\\^^^249|R\r",
+        "R|1|^^^248|5.25|mIU/L||H\\W||F\r",
+        "C|1|I|Sample diluted|G\r",
+        "L|1|N\r"
+    );
+    let message: Message = src.parse().unwrap();
+    let records = Records::try_from(&message).unwrap();
+
+    let value = serde_json::to_value(&records).unwrap();
+    assert_eq!(
+        value["delimiters"],
+        json!({ "field": "|", "repeat": "\\", "component": "^", "escape": "&" })
+    );
+    assert_eq!(value["records"][1]["type"], json!("Patient"));
+    assert_eq!(value["records"][1]["patient_sex"], json!("M"));
+    assert_eq!(value["records"][1]["fields"], json!(19));
+    assert_eq!(
+        value["records"][2]["universal_test_id"][1]["manufacturer_code"],
+        json!(["249"])
+    );
+    assert_eq!(value["records"][3]["abnormal_flags"], json!(["H", "W"]));
+
+    let dst: Records = serde_json::from_value(value).unwrap();
+    assert_eq!(dst, records);
+    assert_eq!(Message::try_from(&dst).unwrap(), message);
+}
+
+#[test]
+fn message_json_round_trip() {
+    let message: Message = "H|\\^&\rL|1|N\r".parse().unwrap();
+
+    let value = serde_json::to_value(&message).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "frames": [
+                { "number": 1, "data": "H|\\^&\r", "last": true },
+                { "number": 2, "data": "L|1|N\r", "last": true }
+            ],
+            "priority": "Routine"
+        })
+    );
+
+    let dst: Message = serde_json::from_value(value).unwrap();
+    assert_eq!(dst, message);
+}
@@ -1,5 +1,5 @@
 use crate::message::*;
-use crate::CharEncoding;
+use crate::{ASTMError, CharEncoding, Deviation, Quirks};
 
 // <STX>5R|2|^^^1.0000+950+1.0|15|||^5^||V||34001637|20080516153540|20080516153602|34001637<CR><ETX>3D<CR><LF>
 fn build_raw_frame() -> Vec<u8> {
@@ -25,7 +25,8 @@ fn build_raw_frame() -> Vec<u8> {
 #[test]
 fn deserialize() {
     let src = build_raw_frame();
-    let frame = Frame::deserialize(&src, CharEncoding::UTF8).unwrap();
+    let (frame, deviations) =
+        Frame::deserialize(&src, CharEncoding::UTF8, &Quirks::default()).unwrap();
 
     assert_eq!(frame.number(), 5);
     assert_eq!(
@@ -33,6 +34,7 @@ fn deserialize() {
         "R|2|^^^1.0000+950+1.0|15|||^5^||V||34001637|20080516153540|20080516153602|34001637\r"
     );
     assert!(frame.is_last());
+    assert!(deviations.is_empty());
 }
 
 #[test]
@@ -50,6 +52,70 @@ fn serialize() {
     assert_eq!(raw, dst);
 }
 
+// <STX>1H|\^&<CR><ETX> followed by the given trailer.
+fn frame_with_trailer(trailer: &[u8]) -> Vec<u8> {
+    let mut dst = b"\x021H|\\^&\r\x03".to_vec();
+    dst.extend(trailer);
+    dst
+}
+
+#[test]
+fn reject_quirks_by_default() {
+    let quirks = Quirks::default();
+
+    for trailer in [
+        &b"e5\r\n"[..],
+        &b"E5\r"[..],
+        &b"B4\r\n"[..],
+        &b" E5\r\n"[..],
+        &b"E5 \r\n"[..],
+    ] {
+        let src = frame_with_trailer(trailer);
+        assert!(Frame::deserialize(&src, CharEncoding::ASCII, &quirks).is_err());
+    }
+}
+
+#[test]
+fn accept_quirks() {
+    let quirks = Quirks {
+        lowercase_checksum: true,
+        missing_lf: true,
+        checksum_without_frame_number: true,
+        stray_whitespace: true,
+    };
+
+    let cases = [
+        (&b"E5\r\n"[..], vec![]),
+        (&b"e5\r\n"[..], vec![Deviation::LowercaseChecksum]),
+        (&b"E5\r"[..], vec![Deviation::MissingLF]),
+        (&b"B4\r\n"[..], vec![Deviation::ChecksumWithoutFrameNumber]),
+        (&b" E5 \r\t\n"[..], vec![Deviation::StrayWhitespace]),
+        (
+            &b"b4 \r"[..],
+            vec![
+                Deviation::StrayWhitespace,
+                Deviation::LowercaseChecksum,
+                Deviation::MissingLF,
+                Deviation::ChecksumWithoutFrameNumber,
+            ],
+        ),
+    ];
+
+    for (trailer, expected) in cases {
+        let src = frame_with_trailer(trailer);
+        let (frame, deviations) = Frame::deserialize(&src, CharEncoding::ASCII, &quirks).unwrap();
+        assert_eq!(frame.data(), "H|\\^&\r");
+        assert_eq!(deviations, expected);
+    }
+
+    // a wrong checksum is still wrong
+    let src = frame_with_trailer(b"E6\r\n");
+    assert_eq!(
+        Frame::deserialize(&src, CharEncoding::ASCII, &quirks),
+        Err(ASTMError::DefectiveFrame("E5".to_string()))
+    );
+}
+
 fn build_raw_message() -> String {
     let mut src =
         "H|\\^&|||Alinity ci-series^2.5^SCM01246|||||||P|LIS2-A2|20190821102030-3000\r".to_string();
@@ -95,3 +161,70 @@ fn to_message() {
 fn from_message() {
     assert_eq!(build_message().to_string(), build_raw_message());
 }
+
+#[test]
+fn frame_number_sequence() {
+    let mut message = Message::default();
+    let frame = |number| Frame {
+        number,
+        data: "L|1|N\r".to_string(),
+        last: true,
+    };
+
+    assert_eq!(message.check_frame_number(&frame(1)), Ok(()));
+    assert_eq!(
+        message.check_frame_number(&frame(2)),
+        Err(ASTMError::OutOfSequenceFrame(1, 2))
+    );
+
+    for number in [1, 2, 3, 4, 5, 6, 7] {
+        message.push_frame(frame(number));
+    }
+
+    assert_eq!(message.check_frame_number(&frame(0)), Ok(()));
+    assert_eq!(
+        message.check_frame_number(&frame(7)),
+        Err(ASTMError::DuplicateFrame(7))
+    );
+    assert_eq!(
+        message.check_frame_number(&frame(3)),
+        Err(ASTMError::OutOfSequenceFrame(0, 3))
+    );
+}
+
+#[test]
+fn split_long_record() {
+    let src = format!("C|1|I|{}|G", "A".repeat(500));
+    let message = Message::from_records([src.as_str()], 240, &CharEncoding::ASCII).unwrap();
+
+    let sizes: Vec<usize> = message.frames.iter().map(|t| t.data.len()).collect();
+    let last: Vec<bool> = message.frames.iter().map(|t| t.is_last()).collect();
+    let numbers: Vec<u8> = message.frames.iter().map(|t| t.number()).collect();
+
+    assert_eq!(sizes, vec![240, 240, 29]);
+    assert_eq!(last, vec![false, false, true]);
+    assert_eq!(numbers, vec![1, 2, 3]);
+    assert_eq!(message.to_string(), format!("{}\r", src));
+
+    let raw = message.frames[0].serialize(CharEncoding::ASCII).unwrap();
+    assert_eq!(raw.len(), 247);
+    assert_eq!(raw[242], 0x17);
+}
+
+#[test]
+fn split_multi_byte_characters() {
+    // 2 bytes per char in UTF-8, 1 byte per char in WINDOWS-1251.
+    let src = "Ж".repeat(200);
+
+    let message = Message::from_records([src.as_str()], 240, &CharEncoding::UTF8).unwrap();
+    let sizes: Vec<usize> = message.frames.iter().map(|t| t.data.len()).collect();
+    assert_eq!(sizes, vec![240, 161]);
+    assert_eq!(message.records(), vec![src.clone()]);
+
+    let message = Message::from_records([src.as_str()], 240, &CharEncoding::WINDOWS_1251).unwrap();
+    assert_eq!(message.frames.len(), 1);
+
+    let message = Message::from_records([src.as_str()], 15, &CharEncoding::UTF8).unwrap();
+    assert!(message.frames.iter().all(|t| t.data.len() <= 15));
+    assert_eq!(message.records(), vec![src]);
+}
@@ -1,2 +1,12 @@
+mod charset;
+mod data_link;
+mod delimiters;
+#[cfg(feature = "serde")]
+mod json;
 mod message;
+mod records;
+mod replay;
+mod token;
+mod trace;
+mod tree;
 mod values;
\ No newline at end of file
@@ -1,7 +1,15 @@
-use chrono::{FixedOffset, TimeZone};
+use chrono::{FixedOffset, NaiveDate, TimeZone};
 
 use crate::records::*;
-use crate::{Message, Records};
+use crate::{ASTMError, Delimiters, FieldError, Message, Records};
+
+// Error of a field that failed to parse.
+fn field_error<T: std::fmt::Debug>(src: crate::Result<T>) -> FieldError {
+    match src {
+        Err(ASTMError::InvalidField(t)) => *t,
+        t => panic!("expected a field error, got {:?}", t),
+    }
+}
 
 #[test]
 fn message_header() {
@@ -11,10 +19,11 @@ fn message_header() {
     let records: Records = message.try_into().unwrap();
     let first = records.into_iter().next().unwrap();
 
-    let dt = FixedOffset::west(10800)
-        .ymd(2019, 8, 21)
-        .and_hms(10, 20, 30);
-    let date_time = ASTMDateTime(dt);
+    let dt = FixedOffset::west_opt(10800)
+        .unwrap()
+        .with_ymd_and_hms(2019, 8, 21, 10, 20, 30)
+        .unwrap();
+    let date_time = ASTMDateTime(dt, true);
 
     assert_eq!(
         first,
@@ -32,6 +41,603 @@ fn message_header() {
             processing_id: Some(ProcessingID::Production),
             version_number: Some("LIS2-A2".to_string()),
             date_and_time_of_message: Some(date_time),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+fn records_round_trip() {
+    let src = "H|\\^&|||Host\rP|1||PID&F&1\rO|1|SID||^^^TSH\rR|1|^^^TSH|1.5|mIU/L\rL|1|N\r";
+    let message: Message = src.parse().unwrap();
+    let records = Records::try_from(&message).unwrap();
+
+    let types: String = records.iter().map(|t| t.record_type()).collect();
+    assert_eq!(types, "HPORL");
+    assert_eq!(
+        records.iter().nth(1),
+        Some(&Record::Patient(PatientRecord {
+            sequence_number: 1,
+            laboratory_assigned_patient_id: Some("PID|1".to_string()),
+            ..Default::default()
+        }))
+    );
+    assert_eq!(Message::try_from(&records).unwrap(), message);
+}
+
+#[test]
+fn dates_and_measurements_layout() {
+    let src = concat!(
+        "H|\\^&|||Host|||||||P|LIS2-A2|20190821102030\r",
+        "P|1||||||19800101|M||||||||170.0^cm|1e2^kg\r",
+        "R|1|^^^TSH|1.50|mIU/L||||F||||20190821102030+0000\r",
+        "L|1|N\r"
+    );
+    let message: Message = src.parse().unwrap();
+    let records = Records::try_from(&message).unwrap();
+
+    let patient = match records.iter().nth(1) {
+        Some(Record::Patient(t)) => t.clone(),
+        _ => panic!("missing patient"),
+    };
+    assert_eq!(patient.patient_height.unwrap().measure, 170.0);
+    assert_eq!(Message::try_from(&records).unwrap(), message);
+}
+
+#[test]
+fn trailing_fields() {
+    let src = concat!(
+        "H|\\^&|||Host|\r",
+        "P|1|||\r",
+        "O|1|SID||^^^TSH^|||\r",
+        "C|1|I|Hemolyzed^|G|\r",
+        "L|1|N|\r"
+    );
+    let message: Message = src.parse().unwrap();
+    let records = Records::try_from(&message).unwrap();
+    assert_eq!(Message::try_from(&records).unwrap(), message);
+
+    let delimiters = Delimiters::default();
+    let terminator = MessageTerminatorRecord::parse("L|1|N|", &delimiters).unwrap();
+    assert_eq!(terminator.fields, 4);
+    assert_eq!(terminator.serialize(&delimiters), "L|1|N|");
+
+    let terminator = MessageTerminatorRecord {
+        sequence_number: 1,
+        ..Default::default()
+    };
+    assert_eq!(terminator.serialize(&delimiters), "L|1");
+}
+
+#[test]
+fn text_fields_with_delimiters() {
+    let delimiters = Delimiters::default();
+    let header = MessageHeaderRecord {
+        sender_name_or_id: Some("A|B\\C^D&E".to_string()),
+        ..Default::default()
+    };
+    let src = header.serialize(&delimiters);
+    assert_eq!(src, "H|\\^&|||A&F&B\\C^D&E&E");
+    assert_eq!(
+        MessageHeaderRecord::parse(&src).unwrap().sender_name_or_id,
+        header.sender_name_or_id
+    );
+
+    let header = MessageHeaderRecord::parse("H|\\^&|||A&F&B").unwrap();
+    assert_eq!(header.sender_name_or_id, Some("A|B".to_string()));
+
+    let result = ResultRecord {
+        sequence_number: 1,
+        measurement_value: Some("<0.5 | >2.0".to_string()),
+        units: Some("mg&dL".to_string()),
+        reference_ranges: Some("0.5 TO 2.0^FASTING\\CHILD".to_string()),
+        ..Default::default()
+    };
+    let src = result.serialize(&delimiters);
+    assert_eq!(src, "R|1||<0.5 &F& >2.0|mg&E&dL|0.5 TO 2.0^FASTING\\CHILD");
+    assert_eq!(ResultRecord::parse(&src, &delimiters).unwrap(), result);
+}
+
+#[test]
+fn records_with_custom_delimiters() {
+    let message: Message = "H!@#$!!!Host#1.0!!!!!!!P\rL!1\r".parse().unwrap();
+    let records = Records::try_from(&message).unwrap();
+    assert_eq!(records.delimiters().component, '#');
+
+    let header = match records.iter().next() {
+        Some(Record::MessageHeader(t)) => t.clone(),
+        _ => panic!("missing header"),
+    };
+    assert_eq!(header.delimiter_definition, Some("@#$".to_string()));
+    assert_eq!(header.sender_name_or_id, Some("Host#1.0".to_string()));
+    assert_eq!(header.processing_id, Some(ProcessingID::Production));
+
+    assert_eq!(
+        records.serialize(),
+        vec!["H!@#$!!!Host#1.0!!!!!!!P".to_string(), "L!1".to_string()]
+    );
+}
+
+#[test]
+fn header_with_custom_delimiters() {
+    let record = Record::parse("H!@#$!!!Host", &Delimiters::default()).unwrap();
+    let header = match record {
+        Record::MessageHeader(t) => t,
+        _ => panic!("missing header"),
+    };
+    assert_eq!(header.delimiter_definition, Some("@#$".to_string()));
+    assert_eq!(header.sender_name_or_id, Some("Host".to_string()));
+
+    let delimiters = Delimiters::from_header("H!@#$").unwrap();
+    let record = Record::parse("H|\\^&|||Host", &delimiters).unwrap();
+    assert_eq!(record.record_type(), 'H');
+
+    assert_eq!(
+        Record::parse("HX|1", &Delimiters::default()),
+        Err(ASTMError::InvalidRecordType("HX".to_string()))
+    );
+}
+
+#[test]
+fn patient() {
+    let src = concat!(
+        "P|1|PRACTICE|LAB||O&S&BRIEN^MARY^A||19650412|F|W|1 Main St^Boston^MA||555-1234|",
+        "DR1\\DR2|||170^cm|68.5^kg|E11.9\\I10|METFORMIN\\LISINOPRIL||||20240101\\20240105|IP|",
+        "WARD 5|||C|M|RI\\ARP|EN"
+    );
+    let delimiters = Delimiters::default();
+    let patient = PatientRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(patient.sequence_number, 1);
+    assert_eq!(
+        patient.patient_name.as_ref().unwrap().last_name,
+        Some("O^BRIEN".to_string())
+    );
+    assert_eq!(
+        patient.birthdate,
+        Some(ASTMDate(NaiveDate::from_ymd_opt(1965, 4, 12).unwrap()))
+    );
+    assert_eq!(patient.patient_sex, Some(PatientSex::Female));
+    assert_eq!(patient.patient_race, Some(PatientRace::White));
+    assert_eq!(
+        patient.patient_address.as_ref().unwrap().city,
+        Some("Boston".to_string())
+    );
+    assert_eq!(
+        patient.attending_physician_id,
+        Repeated::from(vec!["DR1".to_string(), "DR2".to_string()])
+    );
+    assert_eq!(patient.patient_weight.as_ref().unwrap().measure, 68.5);
+    assert_eq!(
+        patient.diagnosis.iter().collect::<Vec<_>>(),
+        vec!["E11.9", "I10"]
+    );
+    assert_eq!(
+        patient.active_medications.iter().collect::<Vec<_>>(),
+        vec!["METFORMIN", "LISINOPRIL"]
+    );
+    assert_eq!(patient.admission_and_discharge_dates.len(), 2);
+    assert_eq!(patient.admission_status, Some(AdmissionStatus::Inpatient));
+    assert_eq!(patient.location, Some("WARD 5".to_string()));
+    assert_eq!(patient.patient_religion, Some(PatientReligion::Catholic));
+    assert_eq!(patient.marital_status, Some(MaritalStatus::Married));
+    assert_eq!(
+        patient.isolation_status,
+        Repeated::from(vec![
+            IsolationStatus::RespiratoryIsolation,
+            IsolationStatus::AntibioticResistancePrecautions
+        ])
+    );
+    assert_eq!(patient.language, Some("EN".to_string()));
+    assert_eq!(patient.dosage_category, None);
+
+    assert_eq!(patient.serialize(&delimiters), src);
+}
+
+#[test]
+fn invalid_patient() {
+    let delimiters = Delimiters::default();
+    assert_eq!(
+        field_error(PatientRecord::parse("P||PID", &delimiters)).error,
+        ASTMError::MissingSequenceNumberValue
+    );
+    assert_eq!(
+        field_error(PatientRecord::parse("P|1|||||||X", &delimiters)),
+        FieldError {
+            record: None,
+            record_type: 'P',
+            field: 8,
+            component: None,
+            text: "X".to_string(),
+            error: ASTMError::InvalidPatientSexValue,
+        }
+    );
+    let src = "P|1|||||||||||||||1,8^m|80^kg";
+    assert_eq!(
+        field_error(PatientRecord::parse(src, &delimiters)),
+        FieldError {
+            record: None,
+            record_type: 'P',
+            field: 16,
+            component: Some(0),
+            text: "1,8".to_string(),
+            error: ASTMError::ParseFloatNumber("1,8".parse::<f64>().unwrap_err()),
+        }
+    );
+}
+
+#[test]
+fn order() {
+    let src = concat!(
+        "O|1|SID001|SID001^12^3|^^^248^TSH^UNDILUTED\\^^^249^FT4|S||20190821101500-0300|||",
+        "|A|||20190821102000-0300|SERUM^VEIN||||||||||Q"
+    );
+    let delimiters = Delimiters::default();
+    let order = OrderRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(order.sequence_number, 1);
+    assert_eq!(
+        order.instrument_specimen_id,
+        Some(SpecimenId {
+            id: Some("SID001".to_string()),
+            rack: Some("12".to_string()),
+            position: Some("3".to_string()),
+            extra: vec![],
+            ..Default::default()
+        })
+    );
+    assert_eq!(order.universal_test_id.len(), 2);
+    assert_eq!(
+        order.universal_test_id.first().unwrap().manufacturer_code,
+        vec![
+            Some("248".to_string()),
+            Some("TSH".to_string()),
+            Some("UNDILUTED".to_string())
+        ]
+    );
+    assert_eq!(
+        order.universal_test_id.first().unwrap().universal_test_id,
+        None
+    );
+    assert_eq!(order.priority, Some(OrderPriority::Stat));
+    assert!(order.specimen_collection_date_and_time.is_some());
+    assert_eq!(order.action_code, Some(ActionCode::Add));
+    assert_eq!(
+        order.specimen_descriptor,
+        Some(SpecimenDescriptor {
+            specimen_type: Some("SERUM".to_string()),
+            specimen_source: Some("VEIN".to_string()),
+            ..Default::default()
+        })
+    );
+    assert_eq!(order.report_type, Some(ReportType::QueryResponse));
+
+    assert_eq!(order.serialize(&delimiters), src);
+}
+
+#[test]
+fn order_with_manufacturer_components() {
+    let src = "O|1|9750230|9750230^L5777^6^1^5|^^^248^TSH^UNDILUTED|R||||||||||||||||||||F";
+    let delimiters = Delimiters::default();
+    let order = OrderRecord::parse(src, &delimiters).unwrap();
+
+    let specimen = order.instrument_specimen_id.as_ref().unwrap();
+    assert_eq!(specimen.rack, Some("L5777".to_string()));
+    assert_eq!(
+        specimen.extra,
+        vec![Some("1".to_string()), Some("5".to_string())]
+    );
+    assert_eq!(order.serialize(&delimiters), src);
+}
+
+#[test]
+fn order_with_repeated_tests() {
+    let src = "O|1|SID001||^^^248\\\\^^^249\\^^^^&R&|R";
+    let delimiters = Delimiters::default();
+    let order = OrderRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(order.universal_test_id.len(), 3);
+    assert_eq!(order.universal_test_id.repeats()[1], None);
+    assert_eq!(
+        order
+            .universal_test_id
+            .iter()
+            .map(|t| t.manufacturer_code[0].as_deref())
+            .collect::<Vec<_>>(),
+        vec![Some("248"), Some("249"), None]
+    );
+    assert_eq!(
+        order.universal_test_id.repeats()[3]
+            .as_ref()
+            .unwrap()
+            .manufacturer_code,
+        vec![None, Some("\\".to_string())]
+    );
+    assert_eq!(order.serialize(&delimiters), src);
+}
+
+#[test]
+fn order_reply() {
+    let order = OrderRecord {
+        sequence_number: 1,
+        specimen_id: Some(SpecimenId {
+            id: Some("SID001".to_string()),
+            ..Default::default()
+        }),
+        universal_test_id: Repeated::from(vec![UniversalTestId {
+            manufacturer_code: vec![Some("248".to_string())],
+            ..Default::default()
+        }]),
+        priority: Some(OrderPriority::Routine),
+        action_code: Some(ActionCode::New),
+        report_type: Some(ReportType::Order),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        order.serialize(&Delimiters::default()),
+        "O|1|SID001||^^^248|R||||||N||||||||||||||O"
+    );
+}
+
+#[test]
+fn result() {
+    let src = concat!(
+        "R|1|^^^248^TSH^UNDILUTED|5.25|mIU/L|0.35 TO 4.94|H\\W||F||ADMIN^ADMIN|",
+        "20190821101500-0300|20190821102030-0300|i1SR01234"
+    );
+    let delimiters = Delimiters::default();
+    let result = ResultRecord::parse(src, &delimiters).unwrap();
+
+    let dt = FixedOffset::west_opt(10800)
+        .unwrap()
+        .with_ymd_and_hms(2019, 8, 21, 10, 20, 30)
+        .unwrap();
+
+    assert_eq!(result.sequence_number, 1);
+    assert_eq!(
+        result.universal_test_id.as_ref().unwrap().manufacturer_code[1],
+        Some("TSH".to_string())
+    );
+    assert_eq!(result.measurement_value, Some("5.25".to_string()));
+    assert_eq!(result.units, Some("mIU/L".to_string()));
+    assert_eq!(result.reference_ranges, Some("0.35 TO 4.94".to_string()));
+    assert_eq!(
+        result.abnormal_flags,
+        Repeated::from(vec![AbnormalFlag::AboveHighNormal, AbnormalFlag::Worse])
+    );
+    assert_eq!(result.result_status, Some(ResultStatus::Final));
+    assert_eq!(
+        result.operator_identification,
+        Some("ADMIN^ADMIN".to_string())
+    );
+    assert_eq!(result.date_and_time_test_completed, Some(ASTMDateTime(dt, true)));
+    assert_eq!(
+        result.instrument_identification,
+        Some("i1SR01234".to_string())
+    );
+
+    assert_eq!(result.serialize(&delimiters), src);
+}
+
+#[test]
+fn invalid_result() {
+    let delimiters = Delimiters::default();
+    assert_eq!(
+        field_error(ResultRecord::parse("R|1|^^^248|1.0|||||Z", &delimiters)).error,
+        ASTMError::InvalidResultStatusValue
+    );
+}
+
+#[test]
+fn comment() {
+    let src = "C|1|I|Hemolyzed &F& retest^SEE NOTE|G";
+    let delimiters = Delimiters::default();
+    let comment = CommentRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(comment.comment_source, Some(CommentSource::Instrument));
+    assert_eq!(
+        comment.comment_text,
+        Some("Hemolyzed | retest^SEE NOTE".to_string())
+    );
+    assert_eq!(comment.comment_type, Some(CommentType::Generic));
+    assert_eq!(comment.serialize(&delimiters), src);
+}
+
+#[test]
+fn comment_with_delimiters() {
+    let delimiters = Delimiters::default();
+    let comment = CommentRecord {
+        sequence_number: 1,
+        comment_source: Some(CommentSource::Instrument),
+        comment_text: Some("a|b\\c^d&e".to_string()),
+        comment_type: Some(CommentType::Generic),
+        ..Default::default()
+    };
+
+    let src = comment.serialize(&delimiters);
+    assert_eq!(src, "C|1|I|a&F&b\\c^d&E&e|G");
+    assert_eq!(CommentRecord::parse(&src, &delimiters).unwrap(), comment);
+}
+
+#[test]
+fn request_information() {
+    let src = "Q|1|^9750230||^^^ALL||||||||O";
+    let delimiters = Delimiters::default();
+    let request = RequestInformationRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(
+        request.starting_range_id,
+        Some(RangeId {
+            patient_id: None,
+            specimen_id: Some("9750230".to_string()),
+            extra: vec![],
+            ..Default::default()
         })
     );
+    assert_eq!(request.ending_range_id, None);
+    assert_eq!(
+        request.universal_test_id.first().unwrap().manufacturer_code,
+        vec![Some("ALL".to_string())]
+    );
+    assert_eq!(
+        request.request_information_status_codes,
+        Repeated::from(vec![RequestStatus::OrdersAndDemographics])
+    );
+    assert_eq!(request.serialize(&delimiters), src);
+}
+
+#[test]
+fn message_terminator() {
+    let delimiters = Delimiters::default();
+    let terminator = MessageTerminatorRecord::parse("L|1|N", &delimiters).unwrap();
+    assert_eq!(terminator.termination_code, Some(TerminationCode::Normal));
+    assert_eq!(terminator.serialize(&delimiters), "L|1|N");
+
+    let terminator = MessageTerminatorRecord::parse("L|1", &delimiters).unwrap();
+    assert_eq!(terminator.termination_code, None);
+    assert_eq!(
+        field_error(MessageTerminatorRecord::parse("L|1|Z", &delimiters)).error,
+        ASTMError::InvalidTerminationCodeValue
+    );
+}
+
+#[test]
+fn manufacturer_information() {
+    let src = "M|1|ABBOTT^QC|LOT 1234||^^^248|";
+    let delimiters = Delimiters::default();
+    let manufacturer = ManufacturerInformationRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(manufacturer.sequence_number, 1);
+    assert_eq!(
+        manufacturer.fields,
+        vec!["ABBOTT^QC", "LOT 1234", "", "^^^248", ""]
+    );
+    assert_eq!(manufacturer.serialize(&delimiters), src);
+
+    let src = "M|1|ABC|||";
+    let manufacturer = ManufacturerInformationRecord::parse(src, &delimiters).unwrap();
+    assert_eq!(manufacturer.fields, vec!["ABC", "", "", ""]);
+    assert_eq!(manufacturer.serialize(&delimiters), src);
+}
+
+#[test]
+fn scientific() {
+    let src = "S|1|IMMUNOASSAY|ALINITY|REAGENT LOT 7|mIU/L||SERUM^VEIN|||SID001|TSH|5.25|mIU/L|||||19921018|F|W";
+    let delimiters = Delimiters::default();
+    let scientific = ScientificRecord::parse(src, &delimiters).unwrap();
+
+    assert_eq!(
+        scientific.analytical_method,
+        Some("IMMUNOASSAY".to_string())
+    );
+    assert_eq!(
+        scientific
+            .specimen_descriptor
+            .as_ref()
+            .unwrap()
+            .specimen_type,
+        Some("SERUM".to_string())
+    );
+    assert_eq!(scientific.analyte, Some("TSH".to_string()));
+    assert_eq!(scientific.result, Some("5.25".to_string()));
+    assert!(scientific.patient_birthdate.is_some());
+    assert_eq!(scientific.patient_sex, Some(PatientSex::Female));
+    assert_eq!(scientific.patient_race, Some(PatientRace::White));
+    assert_eq!(scientific.serialize(&delimiters), src);
+}
+
+#[test]
+fn invalid_records() {
+    let message: Message = "H|\\^&\rX|1\r".parse().unwrap();
+    assert_eq!(
+        field_error(Records::try_from(&message)),
+        FieldError {
+            record: Some(1),
+            record_type: 'X',
+            field: 0,
+            component: None,
+            text: "X|1".to_string(),
+            error: ASTMError::InvalidRecordType("X".to_string()),
+        }
+    );
+
+    let message: Message = "P|1\r".parse().unwrap();
+    assert_eq!(
+        Records::try_from(&message),
+        Err(ASTMError::MissingHeaderRecord)
+    );
+}
+
+#[test]
+fn field_error_position() {
+    let message: Message = concat!(
+        "H|\\^&\r",
+        "P|1|||||||||||||||1,8^m\r",
+        "O|1|SID001||^^^248|R\r",
+        "R|1|^^^248|5.25|||||Z\r",
+        "L|1|N\r"
+    )
+    .parse()
+    .unwrap();
+
+    let error = field_error(Records::try_from(&message));
+    assert_eq!(error.record, Some(1));
+    assert_eq!(error.field, 16);
+    assert_eq!(error.component, Some(0));
+    assert_eq!(
+        error.to_string(),
+        concat!(
+            "Record 1 (P), field 16, component 0 \"1,8\": ",
+            "Could not parse value into float number. invalid float literal"
+        )
+    );
+}
+
+#[test]
+fn lenient_records() {
+    let message: Message = concat!(
+        "H|\\^&\r",
+        "P|1|||||||X||||||||1,8^m\r",
+        "O|1|SID001||^^^248|R\r",
+        "X|1\r",
+        "R||^^^248|5.25|||||Z\r",
+        "L|1|N\r"
+    )
+    .parse()
+    .unwrap();
+
+    let (records, errors) = Records::parse_lenient(&message).unwrap();
+    assert_eq!(records.len(), 5);
+    assert_eq!(
+        errors
+            .iter()
+            .map(|t| (t.record, t.record_type, t.field, t.component))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(1), 'P', 8, None),
+            (Some(1), 'P', 16, Some(0)),
+            (Some(3), 'X', 0, None),
+            (Some(4), 'R', 1, None),
+            (Some(4), 'R', 8, None),
+        ]
+    );
+    assert_eq!(errors[4].text, "Z");
+    assert_eq!(errors[4].error, ASTMError::InvalidResultStatusValue);
+
+    let Some(Record::Patient(patient)) = records.iter().nth(1) else {
+        panic!("expected a patient record");
+    };
+    assert_eq!(patient.patient_sex, None);
+    assert_eq!(patient.patient_height, None);
+    let Some(Record::Result(result)) = records.iter().nth(3) else {
+        panic!("expected a result record");
+    };
+    assert_eq!(result.sequence_number, 0);
+    assert_eq!(result.measurement_value, Some("5.25".to_string()));
+
+    let message: Message = "H|\\^&\rP|1\rL|1\r".parse().unwrap();
+    let (records, errors) = Records::parse_lenient(&message).unwrap();
+    assert_eq!(records.len(), 3);
+    assert!(errors.is_empty());
 }
@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::{
+    Action, CharEncoding, Direction, Entry, Frame, Message, Recording, SocketServer, ASTM,
+};
+
+const ENQ: u8 = 0x05;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const EOT: u8 = 0x04;
+
+#[derive(Clone, Default)]
+struct Instrument {
+    received: Arc<Mutex<Vec<Message>>>,
+}
+
+#[async_trait]
+impl Action<Instrument> for Instrument {
+    async fn on_recv_message(&self, message: &Message) -> Option<Message> {
+        self.received.lock().await.push(message.clone());
+        None
+    }
+}
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2022, 7, 1, 8, 30, 0).unwrap()
+}
+
+fn frame(number: u8, data: &str) -> Vec<u8> {
+    Frame {
+        number,
+        data: data.to_string(),
+        last: true,
+    }
+    .serialize(CharEncoding::ASCII)
+    .unwrap()
+}
+
+fn entry(millis: i64, connection: u64, direction: Direction, data: Vec<u8>) -> Entry {
+    Entry {
+        timestamp: start() + Duration::milliseconds(millis),
+        connection,
+        direction,
+        data,
+    }
+}
+
+fn session(connection: u64) -> Vec<Entry> {
+    vec![
+        entry(0, connection, Direction::In, vec![ENQ]),
+        entry(1, connection, Direction::Out, vec![ACK]),
+        entry(10, connection, Direction::In, frame(1, "H|\\^&\r")),
+        entry(11, connection, Direction::Out, vec![ACK]),
+        entry(20, connection, Direction::In, vec![EOT]),
+    ]
+}
+
+#[tokio::test]
+async fn replay_trace() {
+    let src: String = session(1)
+        .iter()
+        .chain(session(2).iter())
+        .map(|t| format!("{}\n", t))
+        .collect();
+    let recording = Recording::from_trace(&src).unwrap();
+    assert_eq!(recording.connections(), vec![1, 2]);
+
+    let instrument = Instrument::default();
+    let replayed = ASTM::new(instrument.clone())
+        .replay(&recording)
+        .await
+        .unwrap();
+
+    let expected: Vec<Entry> = recording
+        .entries()
+        .iter()
+        .filter(|t| t.direction == Direction::Out)
+        .map(|t| Entry {
+            timestamp: t.timestamp - Duration::milliseconds(1),
+            ..t.clone()
+        })
+        .collect();
+    assert_eq!(replayed, expected);
+
+    let message: Message = "H|\\^&\r".parse().unwrap();
+    assert_eq!(
+        *instrument.received.lock().await,
+        vec![message.clone(), message]
+    );
+}
+
+#[tokio::test]
+async fn replay_timers_on_recorded_time() {
+    let recording = Recording::new(vec![
+        entry(0, 1, Direction::In, vec![ENQ]),
+        entry(45_000, 1, Direction::In, vec![ENQ]),
+    ]);
+
+    let replayed = ASTM::new(Instrument::default())
+        .replay(&recording)
+        .await
+        .unwrap();
+
+    // the receive timeout expires at 30 s, before the second <ENQ>
+    assert_eq!(
+        replayed,
+        vec![
+            entry(0, 1, Direction::Out, vec![ACK]),
+            entry(30_000, 1, Direction::Out, vec![NAK]),
+            entry(45_000, 1, Direction::Out, vec![ACK]),
+        ]
+    );
+}
+
+#[test]
+fn invalid_trace_line() {
+    let src = format!("{}\nnot a trace line\n", session(1)[0]);
+    let error = Recording::from_trace(&src).unwrap_err();
+    assert!(matches!(error, crate::ASTMError::InvalidTraceLine(2, _)));
+}
+
+// Ethernet, IPv4 and TCP headers around a payload.
+fn packet(millis: u32, to_server: bool, seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let (client, server) = ([10, 0, 0, 2], [10, 0, 0, 1]);
+    let (src, dst, src_port, dst_port): ([u8; 4], [u8; 4], u16, u16) = match to_server {
+        true => (client, server, 50000, 7000),
+        false => (server, client, 7000, 50000),
+    };
+
+    let mut tcp = vec![];
+    tcp.extend(src_port.to_be_bytes());
+    tcp.extend(dst_port.to_be_bytes());
+    tcp.extend(seq.to_be_bytes());
+    tcp.extend([0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+    tcp.extend(payload);
+
+    let mut ip = vec![0x45, 0];
+    ip.extend((20 + tcp.len() as u16).to_be_bytes());
+    ip.extend([0, 0, 0, 0, 64, 6, 0, 0]);
+    ip.extend(src);
+    ip.extend(dst);
+    ip.extend(tcp);
+
+    let mut dst = vec![0; 12];
+    dst.extend([0x08, 0x00]);
+    dst.extend(ip);
+    // ethernet padding
+    dst.extend([0; 4]);
+
+    let mut record = vec![];
+    record.extend(1656664200_u32.to_le_bytes());
+    record.extend((millis * 1000).to_le_bytes());
+    record.extend((dst.len() as u32).to_le_bytes());
+    record.extend((dst.len() as u32).to_le_bytes());
+    record.extend(dst);
+    record
+}
+
+#[test]
+fn read_pcap() {
+    let mut src = vec![];
+    src.extend(0xa1b2c3d4_u32.to_le_bytes());
+    src.extend([2, 0, 4, 0]);
+    src.extend([0; 8]);
+    src.extend(65535_u32.to_le_bytes());
+    src.extend(1_u32.to_le_bytes());
+
+    let frame = frame(1, "H|\\^&\r");
+    src.extend(packet(0, true, 100, 0x02, &[]));
+    src.extend(packet(0, false, 500, 0x12, &[]));
+    src.extend(packet(0, true, 101, 0x18, &[ENQ]));
+    src.extend(packet(1, false, 501, 0x18, &[ACK]));
+    // retransmission
+    src.extend(packet(5, true, 101, 0x18, &[ENQ]));
+    src.extend(packet(10, true, 102, 0x18, &frame));
+    src.extend(packet(11, false, 502, 0x18, &[ACK]));
+    src.extend(packet(20, true, 102 + frame.len() as u32, 0x18, &[EOT]));
+
+    let recording = Recording::from_pcap(&src, 7000).unwrap();
+    assert_eq!(recording.entries(), session(1));
+
+    assert!(Recording::from_pcap(&[0; 24], 7000).is_err());
+}
+
+#[tokio::test]
+async fn play_against_server() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let instrument = Instrument::default();
+    let astm = ASTM::new(instrument.clone());
+    tokio::spawn(async move { astm.run(SocketServer::new("127.0.0.1", port)).await });
+
+    let address = format!("127.0.0.1:{}", port).parse().unwrap();
+    while tokio::net::TcpStream::connect(address).await.is_err() {
+        tokio::task::yield_now().await;
+    }
+
+    let recording = Recording::new(session(1));
+    let received = recording.play(address, 10.0).await.unwrap();
+
+    let bytes: Vec<u8> = received.iter().flat_map(|t| t.data.clone()).collect();
+    assert_eq!(bytes, vec![ACK, ACK]);
+    assert!(received.iter().all(|t| t.direction == Direction::Out));
+
+    let message: Message = "H|\\^&\r".parse().unwrap();
+    assert_eq!(*instrument.received.lock().await, vec![message]);
+}
@@ -0,0 +1,121 @@
+use crate::{Frame, Quirks, Token, Tokenizer};
+
+fn frame(number: u8, data: &str, last: bool) -> Vec<u8> {
+    Frame {
+        number,
+        data: data.to_string(),
+        last,
+    }
+    .serialize(crate::CharEncoding::UTF8)
+    .unwrap()
+}
+
+// <ENQ> 1H <ETB> 2H <ETX> 3L <ETX> <EOT>
+fn build_session() -> (Vec<u8>, Vec<Token>) {
+    let first = frame(1, "H|\\^&|||Alinity ci", false);
+    let second = frame(2, "-series\r", true);
+    let third = frame(3, "L|1|N\r", true);
+
+    let mut src = vec![0x05];
+    src.extend_from_slice(&first);
+    src.extend_from_slice(&second);
+    src.extend_from_slice(&third);
+    src.push(0x04);
+
+    let tokens = vec![
+        Token::ENQ,
+        Token::Frame(first),
+        Token::Frame(second),
+        Token::Frame(third),
+        Token::EOT,
+    ];
+
+    (src, tokens)
+}
+
+#[test]
+fn single_chunk() {
+    let (src, tokens) = build_session();
+    let mut tokenizer = Tokenizer::new();
+
+    assert_eq!(tokenizer.decode(&src), tokens);
+    assert!(tokenizer.pending().is_empty());
+}
+
+#[test]
+fn byte_by_byte() {
+    let (src, tokens) = build_session();
+    let mut tokenizer = Tokenizer::new();
+
+    let dst: Vec<Token> = src.iter().flat_map(|t| tokenizer.decode(&[*t])).collect();
+    assert_eq!(dst, tokens);
+}
+
+#[test]
+fn every_chunk_boundary() {
+    let (src, tokens) = build_session();
+
+    for first in 0..src.len() {
+        for second in first..src.len() {
+            let mut tokenizer = Tokenizer::new();
+            let mut dst = tokenizer.decode(&src[..first]);
+            dst.append(&mut tokenizer.decode(&src[first..second]));
+            dst.append(&mut tokenizer.decode(&src[second..]));
+
+            assert_eq!(dst, tokens, "split at {} and {}", first, second);
+            assert!(tokenizer.pending().is_empty());
+        }
+    }
+}
+
+#[test]
+fn interrupted_frame_and_noise() {
+    let mut tokenizer = Tokenizer::new();
+    let complete = frame(2, "L|1|N\r", true);
+
+    let mut src = b"\x02\x31P|1".to_vec();
+    src.push(0x04);
+    src.extend_from_slice(b"noise");
+    src.extend_from_slice(&complete);
+
+    assert_eq!(
+        tokenizer.decode(&src),
+        vec![
+            Token::Frame(b"\x02\x31P|1".to_vec()),
+            Token::EOT,
+            Token::Frame(complete),
+        ]
+    );
+}
+
+#[test]
+fn frames_missing_lf() {
+    let quirks = Quirks {
+        missing_lf: true,
+        ..Default::default()
+    };
+    let mut tokenizer = Tokenizer::with_quirks(&quirks);
+
+    let full = frame(1, "H|\\^&\r", true);
+    let short = full[..full.len() - 1].to_vec();
+
+    // the <CR> in the data does not end the frame
+    assert_eq!(tokenizer.decode(&short[..8]), vec![]);
+    assert_eq!(
+        tokenizer.decode(&short[8..]),
+        vec![Token::Frame(short.clone())]
+    );
+
+    // a late <LF> is not noise
+    let mut src = vec![0x0A];
+    src.extend_from_slice(&full);
+    assert_eq!(tokenizer.decode(&src), vec![Token::Frame(full.clone())]);
+
+    src = short.clone();
+    src.push(0x04);
+    assert_eq!(
+        tokenizer.decode(&src),
+        vec![Token::Frame(short), Token::EOT]
+    );
+    assert!(tokenizer.pending().is_empty());
+}
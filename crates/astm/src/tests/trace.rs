@@ -0,0 +1,126 @@
+use chrono::{TimeZone, Utc};
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::trace::{escape, unescape};
+use crate::{ASTMError, Direction, Entry, Message, Trace, ASTM};
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("astm-trace-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.join("trace.log")
+}
+
+#[derive(Clone)]
+struct Instrument;
+
+#[async_trait::async_trait]
+impl crate::Action<Instrument> for Instrument {
+    async fn on_recv_message(&self, _message: &Message) -> Option<Message> {
+        None
+    }
+}
+
+#[test]
+fn escape_bytes() {
+    assert_eq!(
+        escape(b"\x021H|\\^&\r\x03A5\r\n"),
+        "<STX>1H|\\^&<CR><ETX>A5<CR><LF>"
+    );
+    assert_eq!(escape(&[0x05, 0x17, 0x1F, 0x7F]), "<ENQ><ETB><US><DEL>");
+    assert_eq!(escape(b"a<b>"), "a<x3C>b>");
+    assert_eq!(escape("é".as_bytes()), "<xC3><xA9>");
+}
+
+#[test]
+fn unescape_bytes() {
+    let src = b"\x021H|\\^&\r\x03A5\r\n<a\xC3\xA9\x7F ";
+    assert_eq!(unescape(&escape(src)).unwrap(), src.to_vec());
+
+    assert!(unescape("<STX").is_err());
+    assert!(unescape("<XYZ>").is_err());
+    assert!(unescape("<x1>").is_err());
+    assert!(unescape("é").is_err());
+}
+
+#[test]
+fn format_and_parse_entry() {
+    let timestamp =
+        Utc.with_ymd_and_hms(2022, 7, 1, 8, 30, 0).unwrap() + chrono::Duration::microseconds(123);
+    let entry = Entry {
+        timestamp,
+        connection: 3,
+        direction: Direction::Out,
+        data: b"\x06 x".to_vec(),
+    };
+
+    assert_eq!(
+        entry.to_string(),
+        "2022-07-01T08:30:00.000123Z 3 out <ACK> x"
+    );
+    assert_eq!(entry.to_string().parse::<Entry>().unwrap(), entry);
+
+    assert_eq!(
+        "2022-07-01T08:30:00.000123Z 3 sent <ACK>".parse::<Entry>(),
+        Err(ASTMError::InvalidTraceEntry(
+            "Invalid direction \"sent\".".to_string()
+        ))
+    );
+    assert!("2022-07-01T08:30:00.000123Z 3".parse::<Entry>().is_err());
+}
+
+#[tokio::test]
+async fn capture_connections() {
+    let path = temp_path("capture");
+    let trace = Trace::new(&path);
+
+    for _ in 0..2 {
+        let (local, mut remote) = tokio::io::duplex(1024);
+        let (read, write) = tokio::io::split(local);
+        let astm = ASTM::new(Instrument).trace(trace.clone());
+        let task = tokio::spawn(async move { astm.serve(read, write).await });
+
+        remote.write_all(&[0x05]).await.unwrap();
+        let mut buffer = [0u8; 1];
+        remote.read_exact(&mut buffer).await.unwrap();
+        remote.write_all(&[0x04]).await.unwrap();
+        drop(remote);
+        task.await.unwrap().unwrap();
+    }
+
+    let src = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<Vec<&str>> = src
+        .lines()
+        .map(|t| t.split(' ').skip(1).collect())
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            vec!["1", "in", "<ENQ>"],
+            vec!["1", "out", "<ACK>"],
+            vec!["1", "in", "<EOT>"],
+            vec!["2", "in", "<ENQ>"],
+            vec!["2", "out", "<ACK>"],
+            vec!["2", "in", "<EOT>"],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rotate_by_size() {
+    let path = temp_path("rotate");
+    // every line is 39 bytes long
+    let trace = Trace::new(&path).max_size(100).max_files(2);
+    let capture = trace.connection();
+
+    for _ in 0..7 {
+        capture.record(Direction::In, &[0x05]).await;
+    }
+
+    let size = |t: &str| std::fs::metadata(path.with_file_name(t)).map(|t| t.len());
+    assert_eq!(size("trace.log").unwrap(), 39);
+    assert_eq!(size("trace.log.1").unwrap(), 78);
+    assert_eq!(size("trace.log.2").unwrap(), 78);
+    assert!(size("trace.log.3").is_err());
+}
@@ -0,0 +1,138 @@
+use crate::records::{Problem, StructureProblem};
+use crate::{Message, MessageTree, Records};
+
+fn tree(src: &str) -> MessageTree {
+    let message: Message = src.parse().unwrap();
+    let records = Records::try_from(&message).unwrap();
+    MessageTree::new(&records)
+}
+
+#[test]
+fn walk_message() {
+    let tree = tree(concat!(
+        "H|\\^&\r",
+        "C|1|I|HEADER|G\r",
+        "P|1||PID1\r",
+        "C|1|L|PATIENT|G\r",
+        "O|1|SID1||^^^248\r",
+        "R|1|^^^248|1.5\r",
+        "C|1|I|RESULT|G\r",
+        "C|2|I|RESULT|G\r",
+        "R|2|^^^249|2.5\r",
+        "O|2|SID2||^^^250\r",
+        "P|2||PID2\r",
+        "O|1|SID3||^^^248\r",
+        "R|1|^^^248|3.5\r",
+        "L|1|N\r",
+    ));
+
+    assert!(tree.is_valid(), "{:?}", tree.problems());
+    assert_eq!(tree.header.as_ref().unwrap().comments.len(), 1);
+    assert_eq!(tree.patients.len(), 2);
+
+    let patient = &tree.patients[0];
+    assert_eq!(patient.position, 2);
+    assert_eq!(patient.comments[0].position, 3);
+    assert_eq!(patient.orders.len(), 2);
+    assert_eq!(patient.orders[0].results.len(), 2);
+    assert_eq!(patient.orders[0].results[0].comments.len(), 2);
+    assert!(patient.orders[1].results.is_empty());
+
+    let result = &tree.patients[1].orders[0].results[0];
+    assert_eq!(result.position, 12);
+    assert_eq!(result.record.measurement_value, Some("3.5".to_string()));
+    assert_eq!(tree.terminator.as_ref().unwrap().position, 13);
+}
+
+#[test]
+fn result_without_order() {
+    let tree = tree("H|\\^&\rP|1\rR|1|^^^248|1.5\rC|1|I|ORPHAN|G\rL|1|N\r");
+
+    assert!(tree.patients[0].orders.is_empty());
+    assert_eq!(
+        tree.problems(),
+        &[
+            StructureProblem {
+                position: 2,
+                problem: Problem::MissingParent('R'),
+            },
+            StructureProblem {
+                position: 3,
+                problem: Problem::MissingParent('C'),
+            },
+        ]
+    );
+    assert_eq!(
+        tree.problems()[0].to_string(),
+        "Record 2: R record without a parent record."
+    );
+}
+
+#[test]
+fn sequence_number_gaps() {
+    let tree = tree("H|\\^&\rP|1\rO|1\rO|3\rR|2|^^^248\rP|3\rL|1|N\r");
+
+    assert_eq!(tree.patients.len(), 2);
+    assert_eq!(
+        tree.problems(),
+        &[
+            StructureProblem {
+                position: 3,
+                problem: Problem::SequenceNumber {
+                    record_type: 'O',
+                    expected: 2,
+                    found: 3,
+                },
+            },
+            StructureProblem {
+                position: 4,
+                problem: Problem::SequenceNumber {
+                    record_type: 'R',
+                    expected: 1,
+                    found: 2,
+                },
+            },
+            StructureProblem {
+                position: 5,
+                problem: Problem::SequenceNumber {
+                    record_type: 'P',
+                    expected: 2,
+                    found: 3,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn header_and_terminator_out_of_place() {
+    let tree = tree("H|\\^&\rP|1\rH|\\^&\rL|1|N\rP|2\r");
+    assert_eq!(
+        tree.problems(),
+        &[
+            StructureProblem {
+                position: 2,
+                problem: Problem::UnexpectedHeader,
+            },
+            StructureProblem {
+                position: 4,
+                problem: Problem::AfterTerminator('P'),
+            },
+        ]
+    );
+
+    let tree = MessageTree::new(&Records::default());
+    assert_eq!(
+        tree.problems(),
+        &[
+            StructureProblem {
+                position: 0,
+                problem: Problem::MissingHeader,
+            },
+            StructureProblem {
+                position: 0,
+                problem: Problem::MissingTerminator,
+            },
+        ]
+    );
+}
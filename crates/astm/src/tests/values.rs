@@ -1,5 +1,6 @@
 use chrono::{FixedOffset, TimeZone, NaiveDate};
 use crate::values::*;
+use crate::{ASTMError, Delimiters};
 
 #[test]
 fn address() {
@@ -14,31 +15,53 @@ fn address() {
             state: Some("IL".to_string()),
             postal_code: Some("60305".to_string()),
             country_code: Some("USA".to_string()),
+            ..Default::default()
         }
     );
 }
 
+#[test]
+fn address_with_escapes() {
+    let src = "52 Hilton Street &F& #B42^Chicago^IL";
+    let address: Address = src.parse().unwrap();
+    assert_eq!(
+        address.street_address,
+        Some("52 Hilton Street | #B42".to_string())
+    );
+    assert_eq!(address.postal_code, None);
+    assert_eq!(address.to_string(), src);
+}
+
 #[test]
 fn astm_date_time_with_offset() {
-    let dt = FixedOffset::west(10800)
-        .ymd(2019, 8, 21)
-        .and_hms(10, 20, 30);
-    let date_time_1 = ASTMDateTime(dt);
+    let dt = FixedOffset::west_opt(10800)
+        .unwrap()
+        .with_ymd_and_hms(2019, 8, 21, 10, 20, 30)
+        .unwrap();
+    let date_time_1 = ASTMDateTime(dt, true);
     let date_time_2: ASTMDateTime = "20190821102030-0300".parse().unwrap();
     assert_eq!(date_time_1, date_time_2);
+    assert_eq!(date_time_2.to_string(), "20190821102030-0300");
 }
 
 #[test]
 fn astm_date_time_without_offset() {
-    let dt = FixedOffset::west(0).ymd(2019, 8, 21).and_hms(10, 20, 30);
-    let date_time_1 = ASTMDateTime(dt);
+    let dt = FixedOffset::west_opt(0)
+        .unwrap()
+        .with_ymd_and_hms(2019, 8, 21, 10, 20, 30)
+        .unwrap();
+    let date_time_1 = ASTMDateTime(dt, false);
     let date_time_2: ASTMDateTime = "20190821102030".parse().unwrap();
     assert_eq!(date_time_1, date_time_2);
+    assert_eq!(date_time_2.to_string(), "20190821102030");
+
+    let date_time_3: ASTMDateTime = "20190821102030+0000".parse().unwrap();
+    assert_eq!(date_time_3.to_string(), "20190821102030+0000");
 }
 
 #[test]
 fn astm_date() {
-    let date_1 = ASTMDate(NaiveDate::from_ymd(2019, 8, 21));
+    let date_1 = ASTMDate(NaiveDate::from_ymd_opt(2019, 8, 21).unwrap());
     let date_2: ASTMDate = "20190821".parse().unwrap();
     assert_eq!(date_1, date_2);
 }
@@ -72,10 +95,44 @@ fn patient_name() {
             middle_name: Some("MIDDLE".to_string()),
             suffix: Some("SUFFIX".to_string()),
             title: Some("TITLE".to_string()),
+            ..Default::default()
         }
     );
 }
 
+#[test]
+fn patient_name_with_escapes() {
+    let src = "O&S&BRIEN^MARY &E& ANN";
+    let name: PatientName = src.parse().unwrap();
+    assert_eq!(name.last_name, Some("O^BRIEN".to_string()));
+    assert_eq!(name.first_name, Some("MARY & ANN".to_string()));
+    assert_eq!(name.to_string(), src);
+}
+
+#[test]
+fn patient_name_with_delimiters() {
+    let delimiters = Delimiters {
+        field: '!',
+        repeat: '~',
+        component: '#',
+        escape: '$',
+    };
+    let name = PatientName {
+        last_name: Some("DOE#SMITH".to_string()),
+        first_name: None,
+        middle_name: Some("J^R".to_string()),
+        suffix: None,
+        title: None,
+        ..Default::default()
+    };
+    let dst = name.serialize(&delimiters);
+    assert_eq!(dst, "DOE$S$SMITH##J^R");
+    let parsed = PatientName::parse(&dst, &delimiters).unwrap();
+    assert_eq!(parsed.last_name, name.last_name);
+    assert_eq!(parsed.first_name, None);
+    assert_eq!(parsed.middle_name, name.middle_name);
+}
+
 #[test]
 fn patient_sex() {
     assert_eq!("M".parse::<PatientSex>().unwrap(), PatientSex::Male);
@@ -89,7 +146,24 @@ fn mesaurement() {
     assert_eq!("20^kg".parse::<Measurement>().unwrap(), Measurement {
         measure: 20.0,
         unit: Some("kg".to_string()),
-    })
+        ..Default::default()
+    });
+    assert_eq!("20.5^kg".parse::<Measurement>().unwrap().to_string(), "20.5^kg");
+}
+
+#[test]
+fn measurement_layout() {
+    for src in ["170.0^cm", "1e2^kg", "0.50", "-3.10^mmol/L"] {
+        assert_eq!(src.parse::<Measurement>().unwrap().to_string(), src);
+    }
+    assert_ne!(
+        "170.0^cm".parse::<Measurement>().unwrap(),
+        "170^cm".parse::<Measurement>().unwrap()
+    );
+
+    let mut weight: Measurement = "1e2^kg".parse().unwrap();
+    weight.measure = 68.5;
+    assert_eq!(weight.to_string(), "68.5^kg");
 }
 
 #[test]
@@ -144,4 +218,155 @@ fn marital_status() {
 #[test]
 fn isolation_status() {
     
-}
\ No newline at end of file
+}
+
+#[test]
+fn order_codes() {
+    assert_eq!("C".parse::<OrderPriority>().unwrap(), OrderPriority::CallBack);
+    assert_eq!(OrderPriority::Preoperative.to_string(), "P");
+    assert!("Z".parse::<OrderPriority>().is_err());
+
+    assert_eq!("X".parse::<ActionCode>().unwrap(), ActionCode::InProcess);
+    assert_eq!(ActionCode::QualityControl.to_string(), "Q");
+    assert!("B".parse::<ActionCode>().is_err());
+
+    assert_eq!("Y".parse::<ReportType>().unwrap(), ReportType::NoOrder);
+    assert_eq!(ReportType::Final.to_string(), "F");
+    assert!("A".parse::<ReportType>().is_err());
+}
+
+#[test]
+fn universal_test_id() {
+    let delimiters = Delimiters::default();
+    let test = UniversalTestId::parse("80200^TSH^LN^248", &delimiters).unwrap();
+    assert_eq!(test.universal_test_id, Some("80200".to_string()));
+    assert_eq!(test.universal_test_id_name, Some("TSH".to_string()));
+    assert_eq!(test.universal_test_id_type, Some("LN".to_string()));
+    assert_eq!(test.manufacturer_code, vec![Some("248".to_string())]);
+    assert_eq!(test.serialize(&delimiters), "80200^TSH^LN^248");
+}
+
+#[test]
+fn abnormal_flag() {
+    assert_eq!("LL".parse::<AbnormalFlag>().unwrap(), AbnormalFlag::BelowPanicLow);
+    assert_eq!(">".parse::<AbnormalFlag>().unwrap(), AbnormalFlag::AboveAbsoluteHigh);
+    assert_eq!(
+        "AA".parse::<AbnormalFlag>().unwrap(),
+        AbnormalFlag::Other("AA".to_string())
+    );
+    assert_eq!(AbnormalFlag::SignificantChangeDown.to_string(), "D");
+}
+
+#[test]
+fn result_status() {
+    assert_eq!("V".parse::<ResultStatus>().unwrap(), ResultStatus::Verified);
+    assert_eq!(ResultStatus::CannotBeDone.to_string(), "X");
+    assert!("Y".parse::<ResultStatus>().is_err());
+}
+
+#[test]
+fn comment_and_request_codes() {
+    assert_eq!("L".parse::<CommentSource>().unwrap(), CommentSource::InformationSystem);
+    assert_eq!(CommentType::InstrumentFlag.to_string(), "I");
+    assert!("X".parse::<CommentType>().is_err());
+
+    assert_eq!("A".parse::<RequestStatus>().unwrap(), RequestStatus::Cancel);
+    assert_eq!(RequestStatus::Demographics.to_string(), "D");
+    assert!("Z".parse::<RequestStatus>().is_err());
+
+    assert_eq!("I".parse::<TerminationCode>().unwrap(), TerminationCode::NoInformation);
+    assert_eq!(TerminationCode::QueryProcessed.to_string(), "F");
+}
+
+#[test]
+fn universal_test_id_layout() {
+    let layout = TestIdLayout::new(["assay_number", "assay_name", "dilution"]);
+    let mut test: UniversalTestId = "^^^248^TSH^UNDILUTED".parse().unwrap();
+
+    assert_eq!(test.universal_test_id, None);
+    assert_eq!(test.manufacturer(&layout, "assay_number"), Ok(Some("248")));
+    assert_eq!(test.manufacturer(&layout, "dilution"), Ok(Some("UNDILUTED")));
+    assert_eq!(
+        test.manufacturer(&layout, "lot"),
+        Err(ASTMError::UnknownComponent("lot".to_string()))
+    );
+    assert_eq!(
+        test.named_manufacturer(&layout),
+        vec![
+            ("assay_number", Some("248")),
+            ("assay_name", Some("TSH")),
+            ("dilution", Some("UNDILUTED"))
+        ]
+    );
+
+    test.set_manufacturer(&layout, "dilution", Some("1:10".to_string()))
+        .unwrap();
+    assert_eq!(test.to_string(), "^^^248^TSH^1:10");
+}
+
+#[test]
+fn universal_test_id_build() {
+    let layout = TestIdLayout::new(["assay_number", "assay_name", "dilution"]);
+    let mut test = UniversalTestId::default();
+    test.set_manufacturer(&layout, "assay_name", Some("T^4".to_string()))
+        .unwrap();
+
+    assert_eq!(test.manufacturer(&layout, "assay_number"), Ok(None));
+    assert_eq!(test.to_string(), "^^^^T&S&4");
+    assert_eq!(test.to_string().parse::<UniversalTestId>().unwrap(), test);
+}
+
+#[test]
+fn trailing_components() {
+    let test: UniversalTestId = "^^^TSH^".parse().unwrap();
+    assert_eq!(test.manufacturer_code, vec![Some("TSH".to_string()), None]);
+    assert_eq!(test.to_string(), "^^^TSH^");
+    assert_eq!("80200^TSH^".parse::<UniversalTestId>().unwrap().to_string(), "80200^TSH^");
+    assert_eq!("DOE^JOHN^^".parse::<PatientName>().unwrap().to_string(), "DOE^JOHN^^");
+    assert_eq!("1.8^m^".parse::<Measurement>().unwrap().to_string(), "1.8^m^");
+    assert_eq!("Main St^^^^^^".parse::<Address>().unwrap().to_string(), "Main St^^^^^^");
+
+    let delimiters = Delimiters::default();
+    let tests = Repeated::<UniversalTestId>::parse("^^^248^\\^^^249^^", &delimiters).unwrap();
+    assert_eq!(tests.serialize(&delimiters), "^^^248^\\^^^249^^");
+
+    let name = PatientName {
+        last_name: Some("DOE".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(name.to_string(), "DOE");
+}
+
+#[test]
+fn repeated() {
+    let delimiters = Delimiters::default();
+    let tests = Repeated::<UniversalTestId>::parse("^^^248\\^^^249", &delimiters).unwrap();
+    assert_eq!(tests.len(), 2);
+    assert_eq!(
+        tests.iter().map(|t| t.manufacturer_code[0].as_deref()).collect::<Vec<_>>(),
+        vec![Some("248"), Some("249")]
+    );
+    assert_eq!(tests.serialize(&delimiters), "^^^248\\^^^249");
+
+    let codes = Repeated::<String>::parse("A\\\\B&R&C\\", &delimiters).unwrap();
+    assert_eq!(
+        codes.repeats(),
+        &[Some("A".to_string()), None, Some("B\\C".to_string()), None]
+    );
+    assert_eq!(codes.iter().collect::<Vec<_>>(), vec!["A", "B\\C"]);
+    assert_eq!(codes.serialize(&delimiters), "A\\\\B&R&C\\");
+
+    let flags = Repeated::<AbnormalFlag>::parse("H&R&W\\N", &delimiters).unwrap();
+    assert_eq!(
+        flags.iter().collect::<Vec<_>>(),
+        vec![
+            &AbnormalFlag::Other("H\\W".to_string()),
+            &AbnormalFlag::Normal
+        ]
+    );
+    assert_eq!(flags.serialize(&delimiters), "H&R&W\\N");
+
+    let empty = Repeated::<String>::parse("", &delimiters).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.serialize(&delimiters), "");
+}
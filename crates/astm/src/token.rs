@@ -0,0 +1,141 @@
+use log::debug;
+
+use crate::{ctrl, CtrlChar, Quirks};
+
+/// Frames longer than this are flushed even if the terminator never comes.
+const MAX_FRAME_SIZE: usize = 64000;
+
+/// Link layer unit, as seen by the data link state machine.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    ENQ,
+    ACK,
+    NAK,
+    EOT,
+    /// Raw frame from `<STX>` to `<LF>`. A frame interrupted by another
+    /// control character is emitted as is, so it is rejected with `<NAK>`.
+    Frame(Vec<u8>),
+}
+
+/// Incremental decoder of link layer tokens.
+///
+/// Transports push every chunk they read, no matter where it was cut,
+/// and take the complete tokens out.
+#[derive(Clone, Debug, Default)]
+pub struct Tokenizer {
+    buffer: Vec<u8>,
+    // Frames may end at the <CR> after the checksum.
+    missing_lf: bool,
+    // Last frame ended at the end of a chunk, before its <LF> arrived.
+    skip_lf: bool,
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_quirks(quirks: &Quirks) -> Self {
+        Self {
+            missing_lf: quirks.missing_lf,
+            ..Self::default()
+        }
+    }
+
+    pub fn push(&mut self, src: &[u8]) {
+        let skip = self.skip_lf && src.first() == Some(&ctrl!(LF));
+
+        if !src.is_empty() {
+            self.skip_lf = false;
+        }
+
+        self.buffer.extend_from_slice(&src[skip as usize..]);
+    }
+
+    /// Pushes a chunk and returns every token completed by it.
+    pub fn decode(&mut self, src: &[u8]) -> Vec<Token> {
+        self.push(src);
+        self.collect()
+    }
+
+    /// Bytes received that are not part of a complete token yet.
+    pub fn pending(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn take(&mut self, size: usize) -> Vec<u8> {
+        self.buffer.drain(..size).collect()
+    }
+}
+
+impl Iterator for Tokenizer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            let first = *self.buffer.first()?;
+
+            match first {
+                ctrl!(ENQ) => return Some(self.control(Token::ENQ)),
+                ctrl!(ACK) => return Some(self.control(Token::ACK)),
+                ctrl!(NAK) => return Some(self.control(Token::NAK)),
+                ctrl!(EOT) => return Some(self.control(Token::EOT)),
+                ctrl!(STX) => return self.frame(),
+                _ => {
+                    // noise between tokens
+                    let size = self
+                        .buffer
+                        .iter()
+                        .position(|t| is_token_start(*t))
+                        .unwrap_or(self.buffer.len());
+                    let dropped = self.take(size);
+                    debug!("Dropped {} bytes outside of a frame.", dropped.len());
+                }
+            }
+        }
+    }
+}
+
+impl Tokenizer {
+    fn control(&mut self, token: Token) -> Token {
+        self.take(1);
+        token
+    }
+
+    fn frame(&mut self) -> Option<Token> {
+        let mut trailer = false;
+
+        for (index, char) in self.buffer.iter().enumerate().skip(1) {
+            if *char == ctrl!(LF) {
+                return Some(Token::Frame(self.take(index + 1)));
+            } else if *char == ctrl!(CR) && trailer && self.missing_lf {
+                return match self.buffer.get(index + 1) {
+                    Some(t) if *t == ctrl!(LF) => Some(Token::Frame(self.take(index + 2))),
+                    Some(_) => Some(Token::Frame(self.take(index + 1))),
+                    None => {
+                        self.skip_lf = true;
+                        Some(Token::Frame(self.take(index + 1)))
+                    }
+                };
+            } else if *char == ctrl!(ETX) || *char == ctrl!(ETB) {
+                trailer = true;
+            } else if is_token_start(*char) {
+                return Some(Token::Frame(self.take(index)));
+            }
+        }
+
+        if self.buffer.len() > MAX_FRAME_SIZE {
+            let size = self.buffer.len();
+            return Some(Token::Frame(self.take(size)));
+        }
+
+        None
+    }
+}
+
+fn is_token_start(src: u8) -> bool {
+    matches!(
+        src,
+        ctrl!(STX) | ctrl!(ENQ) | ctrl!(ACK) | ctrl!(NAK) | ctrl!(EOT)
+    )
+}
@@ -0,0 +1,337 @@
+//! Raw wire capture.
+//!
+//! Every chunk read from or written to a connection is appended to the
+//! trace file as one line:
+//!
+//! ```text
+//! 2026-10-17T08:30:00.000123Z 1 in <ENQ>
+//! 2026-10-17T08:30:00.000310Z 1 out <ACK>
+//! 2026-10-17T08:30:00.002201Z 1 in <STX>1H|\^&<CR><ETX>8E<CR><LF>
+//! ```
+//!
+//! Fields are separated by a single space: UTC timestamp with microseconds,
+//! connection id (counted from 1 for each trace), direction (`in` for
+//! received bytes, `out` for sent bytes) and the bytes themselves.
+//! Printable ASCII is written as is, control characters by their ASCII
+//! name in angle brackets and any other byte, `<` included, as `<xHH>`.
+//!
+//! Traces are read back with [`Entry`] or a whole
+//! [`Recording`](crate::Recording) to replay them.
+//!
+//! Once the file grows over the size limit it is renamed to `<file>.1`,
+//! older files are shifted up to `<file>.<max_files>` and dropped after.
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::{ASTMError, Result};
+
+/// Size of the trace file before it is rotated (10 MiB).
+pub const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+/// Number of rotated files kept next to the trace file.
+pub const DEFAULT_MAX_FILES: usize = 5;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+
+const CONTROL_NAMES: [&str; 32] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
+    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+    "FS", "GS", "RS", "US",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    /// Bytes received from the peer.
+    In,
+    /// Bytes sent to the peer.
+    Out,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::In => write!(f, "in"),
+            Self::Out => write!(f, "out"),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "in" => Ok(Self::In),
+            "out" => Ok(Self::Out),
+            _ => Err(invalid(format!("Invalid direction {:?}.", src))),
+        }
+    }
+}
+
+/// Chunk of bytes read or written on a connection, a line of the trace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub timestamp: DateTime<Utc>,
+    pub connection: u64,
+    pub direction: Direction,
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.timestamp.format(TIMESTAMP_FORMAT),
+            self.connection,
+            self.direction,
+            escape(&self.data)
+        )
+    }
+}
+
+impl FromStr for Entry {
+    type Err = ASTMError;
+
+    /// Parses a line, without the line feed.
+    fn from_str(src: &str) -> Result<Self> {
+        let mut fields = src.splitn(4, ' ');
+        let mut next = |name: &str| {
+            fields
+                .next()
+                .ok_or_else(|| invalid(format!("Missing {}.", name)))
+        };
+
+        let timestamp = next("timestamp")?;
+        let timestamp = DateTime::parse_from_rfc3339(timestamp)
+            .map_err(|t| invalid(format!("Invalid timestamp {:?}. {}", timestamp, t)))?
+            .with_timezone(&Utc);
+        let connection = next("connection")?;
+        let connection = connection
+            .parse()
+            .map_err(|_| invalid(format!("Invalid connection {:?}.", connection)))?;
+        let direction = next("direction")?.parse()?;
+        let data = unescape(next("data")?)?;
+
+        Ok(Self {
+            timestamp,
+            connection,
+            direction,
+            data,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    file: Option<File>,
+    size: u64,
+}
+
+/// Trace file shared by every connection of an instrument.
+#[derive(Clone)]
+pub struct Trace {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    writer: Arc<Mutex<Writer>>,
+    connections: Arc<AtomicU64>,
+}
+
+impl Trace {
+    /// The file and its folder are created on the first write.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            max_size: DEFAULT_MAX_SIZE,
+            max_files: DEFAULT_MAX_FILES,
+            writer: Arc::default(),
+            connections: Arc::default(),
+        }
+    }
+
+    pub fn max_size(mut self, src: u64) -> Self {
+        self.max_size = src;
+        self
+    }
+
+    pub fn max_files(mut self, src: usize) -> Self {
+        self.max_files = src;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Starts the capture of a new connection.
+    pub(crate) fn connection(&self) -> Capture {
+        Capture {
+            id: self.connections.fetch_add(1, Ordering::Relaxed) + 1,
+            trace: self.clone(),
+        }
+    }
+
+    async fn write(&self, line: &str) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        let size = line.len() as u64;
+
+        if writer.size > 0 && writer.size + size > self.max_size {
+            writer.file = None;
+            self.rotate().await?;
+            writer.size = 0;
+        }
+
+        if writer.file.is_none() {
+            if let Some(parent) = self.path.parent().filter(|t| !t.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .await
+                    .map_err(|t| ASTMError::Trace(t.to_string()))?;
+            }
+
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await
+                .map_err(|t| ASTMError::Trace(t.to_string()))?;
+            writer.size = file
+                .metadata()
+                .await
+                .map_err(|t| ASTMError::Trace(t.to_string()))?
+                .len();
+            writer.file = Some(file);
+        }
+
+        if let Some(file) = writer.file.as_mut() {
+            file.write_all(line.as_bytes())
+                .await
+                .map_err(|t| ASTMError::Trace(t.to_string()))?;
+            file.flush()
+                .await
+                .map_err(|t| ASTMError::Trace(t.to_string()))?;
+        }
+
+        writer.size += size;
+        Ok(())
+    }
+
+    async fn rotate(&self) -> Result<()> {
+        if self.max_files == 0 {
+            return ignore_missing(fs::remove_file(&self.path).await);
+        }
+
+        for index in (1..self.max_files).rev() {
+            let src = self.rotated_path(index);
+            let dst = self.rotated_path(index + 1);
+            ignore_missing(fs::rename(src, dst).await)?;
+        }
+
+        ignore_missing(fs::rename(&self.path, self.rotated_path(1)).await)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut dst = OsString::from(self.path.as_os_str());
+        dst.push(format!(".{}", index));
+        dst.into()
+    }
+}
+
+fn ignore_missing(src: std::io::Result<()>) -> Result<()> {
+    match src {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(ASTMError::Trace(err.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Capture of a single connection.
+pub(crate) struct Capture {
+    id: u64,
+    trace: Trace,
+}
+
+impl Capture {
+    /// Failures are logged, the connection goes on without the line.
+    pub(crate) async fn record(&self, direction: Direction, src: &[u8]) {
+        let entry = Entry {
+            timestamp: Utc::now(),
+            connection: self.id,
+            direction,
+            data: src.to_vec(),
+        };
+        let line = format!("{}\n", entry);
+
+        if let Err(err) = self.trace.write(&line).await {
+            warn!("Could not write trace {:?}. {}", self.trace.path, err);
+        }
+    }
+}
+
+/// Writes bytes in the trace notation.
+pub fn escape(src: &[u8]) -> String {
+    let mut dst = String::with_capacity(src.len());
+
+    for byte in src {
+        match byte {
+            0..=0x1F => {
+                dst.push('<');
+                dst.push_str(CONTROL_NAMES[*byte as usize]);
+                dst.push('>');
+            }
+            0x7F => dst.push_str("<DEL>"),
+            b'<' | 0x80..=0xFF => dst.push_str(&format!("<x{:02X}>", byte)),
+            _ => dst.push(*byte as char),
+        }
+    }
+
+    dst
+}
+
+fn invalid(src: String) -> ASTMError {
+    ASTMError::InvalidTraceEntry(src)
+}
+
+/// Reads bytes written in the trace notation.
+pub fn unescape(src: &str) -> Result<Vec<u8>> {
+    let mut dst = Vec::with_capacity(src.len());
+    let mut rest = src;
+
+    while let Some(char) = rest.chars().next() {
+        if char == '<' {
+            let end = rest
+                .find('>')
+                .ok_or_else(|| invalid(format!("Unterminated byte name {:?}.", rest)))?;
+            let name = &rest[1..end];
+
+            let byte = match CONTROL_NAMES.iter().position(|t| *t == name) {
+                Some(t) => t as u8,
+                None if name == "DEL" => 0x7F,
+                None => name
+                    .strip_prefix('x')
+                    .filter(|t| t.len() == 2)
+                    .and_then(|t| u8::from_str_radix(t, 16).ok())
+                    .ok_or_else(|| invalid(format!("Invalid byte name <{}>.", name)))?,
+            };
+
+            dst.push(byte);
+            rest = &rest[end + 1..];
+        } else if char.is_ascii() && !char.is_ascii_control() {
+            dst.push(char as u8);
+            rest = &rest[1..];
+        } else {
+            return Err(invalid(format!("Invalid character {:?}.", char)));
+        }
+    }
+
+    Ok(dst)
+}
@@ -1,85 +1,365 @@
 use chrono::{DateTime, FixedOffset, NaiveDate};
 use std::str::FromStr;
 
-use crate::{ASTMError, Result};
+use crate::{ASTMError, Delimiters, Result};
+
+/// Value of a field or a component, written with the delimiters of the
+/// message. Text is unescaped when parsed and escaped when serialized.
+pub trait Value: Sized {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self>;
+
+    fn serialize(&self, delimiters: &Delimiters) -> String;
+}
+
+// Unescaped components of a value, `None` when empty.
+fn components(src: &str, delimiters: &Delimiters) -> Vec<Option<String>> {
+    src.split(delimiters.component)
+        .map(|t| Some(delimiters.unescape(t)).filter(|t| !t.is_empty()))
+        .collect()
+}
+
+fn component(src: &[Option<String>], index: usize) -> Option<String> {
+    src.get(index).cloned().flatten()
+}
+
+// Number of components or fields parsed when the last ones are empty,
+// so that they are written back, 0 otherwise.
+pub(crate) fn trailing<T>(src: &[T], empty: impl Fn(&T) -> bool) -> usize {
+    match src.last() {
+        Some(t) if empty(t) => src.len(),
+        _ => 0,
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn is_zero(src: &usize) -> bool {
+    *src == 0
+}
+
+// Escaped components joined, leaving out the missing ones at the end
+// past the number of components parsed.
+fn join(src: &[Option<&str>], components: usize, delimiters: &Delimiters) -> String {
+    let size = src.iter().rposition(Option::is_some).map_or(0, |t| t + 1);
+
+    (0..size.max(components))
+        .map(|t| src.get(t).copied().flatten())
+        .map(|t| t.map(|t| delimiters.escape(t)).unwrap_or_default())
+        .collect::<Vec<String>>()
+        .join(&delimiters.component.to_string())
+}
+
+// Applies a function to every component of a text, keeping the
+// component delimiters between them.
+fn map_components(src: &str, delimiters: &Delimiters, f: impl Fn(&str) -> String) -> String {
+    src.split(delimiters.component)
+        .map(f)
+        .collect::<Vec<String>>()
+        .join(&delimiters.component.to_string())
+}
+
+/// Text of a field, or of one of its repeats, unescaped. Components are
+/// kept, separated by the component delimiter, so `&S&` is written back
+/// as the delimiter itself.
+impl Value for String {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        Ok(map_components(src, delimiters, |t| delimiters.unescape(t)))
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        map_components(self, delimiters, |t| delimiters.escape(t))
+    }
+}
+
+// Text of a field that is not split in repeats, unescaped, keeping its
+// repeat delimiters.
+pub(crate) fn parse_text(src: &str, delimiters: &Delimiters) -> String {
+    src.split(delimiters.repeat)
+        .map(|t| map_components(t, delimiters, |t| delimiters.unescape(t)))
+        .collect::<Vec<String>>()
+        .join(&delimiters.repeat.to_string())
+}
+
+pub(crate) fn serialize_text(src: &str, delimiters: &Delimiters) -> String {
+    src.split(delimiters.repeat)
+        .map(|t| map_components(t, delimiters, |t| delimiters.escape(t)))
+        .collect::<Vec<String>>()
+        .join(&delimiters.repeat.to_string())
+}
+
+// Values that are a single component, parsed from their unescaped text.
+// With serde they are written as that text, such as `"F"` or
+// `"20190821102030-0300"`.
+macro_rules! single_value {
+    ($($name: ident),*) => {
+        $(
+            impl Value for $name {
+                fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+                    delimiters.unescape(src).parse()
+                }
+
+                fn serialize(&self, delimiters: &Delimiters) -> String {
+                    delimiters.escape(&self.to_string())
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl serde::Serialize for $name {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> std::result::Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de> serde::Deserialize<'de> for $name {
+                fn deserialize<D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> std::result::Result<Self, D::Error> {
+                    let src = String::deserialize(deserializer)?;
+                    src.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+single_value!(
+    ASTMDateTime,
+    ASTMDate,
+    ProcessingID,
+    PatientSex,
+    PatientRace,
+    AdmissionStatus,
+    PatientReligion,
+    MaritalStatus,
+    IsolationStatus,
+    OrderPriority,
+    ActionCode,
+    ReportType,
+    AbnormalFlag,
+    ResultStatus,
+    CommentSource,
+    CommentType,
+    RequestStatus,
+    TerminationCode
+);
+
+/* Repeated */
+
+/// Values of a field separated by the repeat delimiter, such as the tests
+/// of an order `^^^248\^^^249`. Empty repeats are kept, so the field is
+/// written back with the layout it was sent with.
+///
+/// An escaped repeat delimiter is `&R&` and not the delimiter itself, so
+/// it stays in its value and is unescaped with it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Repeated<T>(Vec<Option<T>>);
+
+impl<T> Repeated<T> {
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    /// Values, leaving out the empty repeats.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.iter().flatten()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    /// Number of values, leaving out the empty repeats.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&mut self, src: T) {
+        self.0.push(Some(src));
+    }
+
+    /// Every repeat, `None` when empty.
+    pub fn repeats(&self) -> &[Option<T>] {
+        &self.0
+    }
+}
+
+impl<T> Default for Repeated<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for Repeated<T> {
+    fn from(src: Vec<T>) -> Self {
+        Self(src.into_iter().map(Some).collect())
+    }
+}
+
+impl<T> FromIterator<T> for Repeated<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(src: I) -> Self {
+        Self(src.into_iter().map(Some).collect())
+    }
+}
+
+impl<T: Value> Value for Repeated<T> {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        if src.is_empty() {
+            return Ok(Self::new());
+        }
+
+        src.split(delimiters.repeat)
+            .map(|t| match t.is_empty() {
+                true => Ok(None),
+                false => T::parse(t, delimiters).map(Some),
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        self.0
+            .iter()
+            .map(|t| {
+                t.as_ref()
+                    .map(|t| t.serialize(delimiters))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<String>>()
+            .join(&delimiters.repeat.to_string())
+    }
+}
 
 /* Address */
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Address {
     pub street_address: Option<String>,
     pub city: Option<String>,
     pub state: Option<String>,
     pub postal_code: Option<String>,
     pub country_code: Option<String>,
+    /// Components parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub components: usize,
+}
+
+impl Value for Address {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+
+        Ok(Address {
+            street_address: component(&dst, 0),
+            city: component(&dst, 1),
+            state: component(&dst, 2),
+            postal_code: component(&dst, 3),
+            country_code: component(&dst, 4),
+            components: trailing(&dst, Option::is_none),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        join(
+            &[
+                self.street_address.as_deref(),
+                self.city.as_deref(),
+                self.state.as_deref(),
+                self.postal_code.as_deref(),
+                self.country_code.as_deref(),
+            ],
+            self.components,
+            delimiters,
+        )
+    }
 }
 
 impl FromStr for Address {
     type Err = ASTMError;
 
     fn from_str(src: &str) -> Result<Self> {
-        let dst: Vec<&str> = src.split("^").collect();
+        Self::parse(src, &Delimiters::default())
+    }
+}
 
-        Ok(Address {
-            street_address: dst.get(0).map(|t| t.to_string()),
-            city: dst.get(1).map(|t| t.to_string()),
-            state: dst.get(2).map(|t| t.to_string()),
-            postal_code: dst.get(3).map(|t| t.to_string()),
-            country_code: dst.get(4).map(|t| t.to_string()),
-        })
+impl std::fmt::Display for Address {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str(&self.serialize(&Delimiters::default()))
     }
 }
 
 /* Dates and Times */
 
-#[derive(Debug, PartialEq)]
-pub struct ASTMDateTime(pub(crate) DateTime<FixedOffset>);
+/// Date and time, with a flag telling whether the source had an offset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ASTMDateTime(pub(crate) DateTime<FixedOffset>, pub(crate) bool);
 
 impl FromStr for ASTMDateTime {
     type Err = ASTMError;
 
     fn from_str(src: &str) -> Result<Self> {
-        match DateTime::parse_from_str(&src, "%Y%m%d%H%M%S%z") {
-            Ok(t) => Ok(ASTMDateTime(t)),
+        match DateTime::parse_from_str(src, "%Y%m%d%H%M%S%z") {
+            Ok(t) => Ok(ASTMDateTime(t, true)),
             Err(_) => {
                 let dst = format!("{}+0000", src);
                 DateTime::parse_from_str(&dst, "%Y%m%d%H%M%S%z")
                     .map_err(ASTMError::InvalidDateAndTimeValue)
-                    .map(ASTMDateTime)
+                    .map(|t| ASTMDateTime(t, false))
             }
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl std::fmt::Display for ASTMDateTime {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.1 {
+            true => write!(fmt, "{}", self.0.format("%Y%m%d%H%M%S%z")),
+            false => write!(fmt, "{}", self.0.format("%Y%m%d%H%M%S")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct ASTMDate(pub(crate) NaiveDate);
 
 impl FromStr for ASTMDate {
     type Err = ASTMError;
 
     fn from_str(src: &str) -> Result<Self> {
-        NaiveDate::parse_from_str(&src, "%Y%m%d")
+        NaiveDate::parse_from_str(src, "%Y%m%d")
             .map_err(ASTMError::InvalidDateAndTimeValue)
             .map(ASTMDate)
     }
 }
 
+impl std::fmt::Display for ASTMDate {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "{}", self.0.format("%Y%m%d"))
+    }
+}
+
 /* Processing ID */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum ProcessingID {
+    #[default]
     Production,
     Training,
     Debugging,
     QualityControl,
 }
 
-impl Default for ProcessingID {
-    fn default() -> Self {
-        Self::Production
-    }
-}
-
 impl FromStr for ProcessingID {
     type Err = ASTMError;
 
@@ -94,48 +374,86 @@ impl FromStr for ProcessingID {
     }
 }
 
+impl std::fmt::Display for ProcessingID {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Production => fmt.write_str("P")?,
+            Self::Training => fmt.write_str("T")?,
+            Self::Debugging => fmt.write_str("D")?,
+            Self::QualityControl => fmt.write_str("Q")?,
+        }
+        Ok(())
+    }
+}
+
 /* Patient Name */
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PatientName {
     pub last_name: Option<String>,
     pub first_name: Option<String>,
     pub middle_name: Option<String>,
     pub suffix: Option<String>,
     pub title: Option<String>,
+    /// Components parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub components: usize,
+}
+
+impl Value for PatientName {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+
+        Ok(PatientName {
+            last_name: component(&dst, 0),
+            first_name: component(&dst, 1),
+            middle_name: component(&dst, 2),
+            suffix: component(&dst, 3),
+            title: component(&dst, 4),
+            components: trailing(&dst, Option::is_none),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        join(
+            &[
+                self.last_name.as_deref(),
+                self.first_name.as_deref(),
+                self.middle_name.as_deref(),
+                self.suffix.as_deref(),
+                self.title.as_deref(),
+            ],
+            self.components,
+            delimiters,
+        )
+    }
 }
 
 impl FromStr for PatientName {
     type Err = ASTMError;
 
     fn from_str(src: &str) -> Result<Self> {
-        let dst: Vec<&str> = src.split("^").collect();
+        Self::parse(src, &Delimiters::default())
+    }
+}
 
-        Ok(PatientName {
-            last_name: dst.get(0).map(|t| t.to_string()),
-            first_name: dst.get(1).map(|t| t.to_string()),
-            middle_name: dst.get(2).map(|t| t.to_string()),
-            suffix: dst.get(3).map(|t| t.to_string()),
-            title: dst.get(4).map(|t| t.to_string()),
-        })
+impl std::fmt::Display for PatientName {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str(&self.serialize(&Delimiters::default()))
     }
 }
 
 /* Patient Sex */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum PatientSex {
     Male,
     Female,
+    #[default]
     Unknown,
 }
 
-impl Default for PatientSex {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl FromStr for PatientSex {
     type Err = ASTMError;
 
@@ -162,7 +480,7 @@ impl std::fmt::Display for PatientSex {
 
 /* Patient Race */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PatientRace {
     White,
     Black,
@@ -203,29 +521,73 @@ impl std::fmt::Display for PatientRace {
 
 /* Measurement */
 
-#[derive(Debug, Default, PartialEq)]
+/// Measure and unit. The measure is written back as it was parsed, such
+/// as `170.0` or `1e2`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Measurement {
     pub(crate) measure: f64,
     pub(crate) unit: Option<String>,
+    /// Text of the measure when it is not written as `measure` would be.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub(crate) text: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub(crate) components: usize,
+}
+
+impl Value for Measurement {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+        let value = component(&dst, 0).unwrap_or_default();
+        let measure = match value.is_empty() {
+            true => Err(ASTMError::MissingMeasurementValue),
+            false => value.parse::<f64>().map_err(ASTMError::ParseFloatNumber),
+        }
+        .map_err(|t| ASTMError::InvalidComponent {
+            component: 0,
+            text: value.clone(),
+            error: Box::new(t),
+        })?;
+
+        Ok(Self {
+            measure,
+            unit: component(&dst, 1),
+            text: Some(value).filter(|t| *t != measure.to_string()),
+            components: trailing(&dst, Option::is_none),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        // The text is left out when the measure was changed after parsing.
+        let measure = match &self.text {
+            Some(t) if t.parse() == Ok(self.measure) => t.clone(),
+            _ => self.measure.to_string(),
+        };
+        join(
+            &[Some(measure.as_str()), self.unit.as_deref()],
+            self.components,
+            delimiters,
+        )
+    }
 }
 
 impl FromStr for Measurement {
     type Err = ASTMError;
 
     fn from_str(src: &str) -> Result<Self> {
-        let dst: Vec<&str> = src.split("^").collect();
-        let value = dst.get(0).ok_or(ASTMError::MissingMeasurementValue)?;
+        Self::parse(src, &Delimiters::default())
+    }
+}
 
-        Ok(Self {
-            measure: value.parse::<f64>().map_err(ASTMError::ParseFloatNumber)?,
-            unit: dst.get(1).map(|t| t.to_string()),
-        })
+impl std::fmt::Display for Measurement {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str(&self.serialize(&Delimiters::default()))
     }
 }
 
 /* Admission Status */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AdmissionStatus {
     Outpatient,
     Preadmit,
@@ -263,7 +625,7 @@ impl std::fmt::Display for AdmissionStatus {
 
 /* Patient Religion */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PatientReligion {
     Protestant,
     Catholic,
@@ -307,7 +669,7 @@ impl std::fmt::Display for PatientReligion {
 
 /* Marital Status */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum MaritalStatus {
     Married,
     Single,
@@ -346,7 +708,7 @@ impl std::fmt::Display for MaritalStatus {
 
 /* Isolation Status */
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum IsolationStatus {
     AntibioticResistancePrecautions,
     BloodAndNeedlePrecautions,
@@ -391,8 +753,682 @@ impl std::fmt::Display for IsolationStatus {
             Self::SecretionExcretionPrecautions => fmt.write_str("SE")?,
             Self::StrictIsolation => fmt.write_str("SI")?,
             Self::WoundAndSkinPrecautions => fmt.write_str("WSP")?,
-            Self::Other(t) => fmt.write_str(&t)?,
+            Self::Other(t) => fmt.write_str(t)?,
+        }
+        Ok(())
+    }
+}
+
+/* Specimen ID */
+
+/// Specimen ID, with the rack and the position of the specimen on the
+/// instrument when given.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpecimenId {
+    pub id: Option<String>,
+    pub rack: Option<String>,
+    pub position: Option<String>,
+    /// Components after the position, defined by the manufacturer.
+    pub extra: Vec<Option<String>>,
+    /// Components parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub components: usize,
+}
+
+impl Value for SpecimenId {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+
+        Ok(Self {
+            id: component(&dst, 0),
+            rack: component(&dst, 1),
+            position: component(&dst, 2),
+            components: trailing(&dst, Option::is_none),
+            extra: dst.into_iter().skip(3).collect(),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = vec![
+            self.id.as_deref(),
+            self.rack.as_deref(),
+            self.position.as_deref(),
+        ];
+        dst.extend(self.extra.iter().map(|t| t.as_deref()));
+        join(&dst, self.components, delimiters)
+    }
+}
+
+/* Universal Test ID */
+
+/// Test of an order or a result, `80200^TSH^LN` or `^^^248^TSH^UNDILUTED`.
+/// The universal ID, name and type are followed by the manufacturer's or
+/// local code, in components defined by each instrument and named with a
+/// `TestIdLayout`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UniversalTestId {
+    pub universal_test_id: Option<String>,
+    pub universal_test_id_name: Option<String>,
+    pub universal_test_id_type: Option<String>,
+    /// Manufacturer's or local code, every component from the fourth.
+    pub manufacturer_code: Vec<Option<String>>,
+    /// Components parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub components: usize,
+}
+
+impl UniversalTestId {
+    /// Manufacturer component with the given name in the layout.
+    pub fn manufacturer(&self, layout: &TestIdLayout, name: &str) -> Result<Option<&str>> {
+        let index = layout.index(name)?;
+        Ok(self.manufacturer_code.get(index).and_then(|t| t.as_deref()))
+    }
+
+    /// Sets the manufacturer component with the given name in the layout,
+    /// adding empty components before it when missing.
+    pub fn set_manufacturer(
+        &mut self,
+        layout: &TestIdLayout,
+        name: &str,
+        value: Option<String>,
+    ) -> Result<()> {
+        let index = layout.index(name)?;
+
+        if self.manufacturer_code.len() <= index {
+            self.manufacturer_code.resize(index + 1, None);
         }
+
+        self.manufacturer_code[index] = value;
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Manufacturer components paired with their names in the layout.
+    /// Components the layout does not name are left out.
+    pub fn named_manufacturer<'a>(
+        &'a self,
+        layout: &'a TestIdLayout,
+    ) -> Vec<(&'a str, Option<&'a str>)> {
+        layout
+            .names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let value = self.manufacturer_code.get(index).and_then(|t| t.as_deref());
+                (name.as_str(), value)
+            })
+            .collect()
+    }
+}
+
+impl Value for UniversalTestId {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+
+        Ok(Self {
+            universal_test_id: component(&dst, 0),
+            universal_test_id_name: component(&dst, 1),
+            universal_test_id_type: component(&dst, 2),
+            components: trailing(&dst, Option::is_none),
+            manufacturer_code: dst.into_iter().skip(3).collect(),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = vec![
+            self.universal_test_id.as_deref(),
+            self.universal_test_id_name.as_deref(),
+            self.universal_test_id_type.as_deref(),
+        ];
+        dst.extend(self.manufacturer_code.iter().map(|t| t.as_deref()));
+        join(&dst, self.components, delimiters)
+    }
+}
+
+impl FromStr for UniversalTestId {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        Self::parse(src, &Delimiters::default())
+    }
+}
+
+impl std::fmt::Display for UniversalTestId {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str(&self.serialize(&Delimiters::default()))
+    }
+}
+
+/// Names of the manufacturer components of a universal test ID, as
+/// defined by an instrument. The Alinity `^^^248^TSH^UNDILUTED` is named
+/// by `["assay_number", "assay_name", "dilution"]`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestIdLayout {
+    names: Vec<String>,
+}
+
+impl TestIdLayout {
+    pub fn new<T: Into<String>>(names: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            names: names.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    fn index(&self, name: &str) -> Result<usize> {
+        self.names
+            .iter()
+            .position(|t| t == name)
+            .ok_or_else(|| ASTMError::UnknownComponent(name.to_string()))
+    }
+}
+
+/* Order Priority */
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum OrderPriority {
+    Stat,
+    Asap,
+    #[default]
+    Routine,
+    CallBack,
+    Preoperative,
+}
+
+impl FromStr for OrderPriority {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "S" => Ok(Self::Stat),
+            "A" => Ok(Self::Asap),
+            "R" => Ok(Self::Routine),
+            "C" => Ok(Self::CallBack),
+            "P" => Ok(Self::Preoperative),
+            _ => Err(ASTMError::InvalidOrderPriorityValue),
+        }
+    }
+}
+
+impl std::fmt::Display for OrderPriority {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Stat => fmt.write_str("S")?,
+            Self::Asap => fmt.write_str("A")?,
+            Self::Routine => fmt.write_str("R")?,
+            Self::CallBack => fmt.write_str("C")?,
+            Self::Preoperative => fmt.write_str("P")?,
+        }
+        Ok(())
+    }
+}
+
+/* Action Code */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionCode {
+    Cancel,
+    Add,
+    New,
+    Pending,
+    Reserved,
+    InProcess,
+    QualityControl,
+}
+
+impl FromStr for ActionCode {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "C" => Ok(Self::Cancel),
+            "A" => Ok(Self::Add),
+            "N" => Ok(Self::New),
+            "P" => Ok(Self::Pending),
+            "L" => Ok(Self::Reserved),
+            "X" => Ok(Self::InProcess),
+            "Q" => Ok(Self::QualityControl),
+            _ => Err(ASTMError::InvalidActionCodeValue),
+        }
+    }
+}
+
+impl std::fmt::Display for ActionCode {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Cancel => fmt.write_str("C")?,
+            Self::Add => fmt.write_str("A")?,
+            Self::New => fmt.write_str("N")?,
+            Self::Pending => fmt.write_str("P")?,
+            Self::Reserved => fmt.write_str("L")?,
+            Self::InProcess => fmt.write_str("X")?,
+            Self::QualityControl => fmt.write_str("Q")?,
+        }
+        Ok(())
+    }
+}
+
+/* Specimen Descriptor */
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpecimenDescriptor {
+    pub specimen_type: Option<String>,
+    pub specimen_source: Option<String>,
+    /// Components parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub components: usize,
+}
+
+impl Value for SpecimenDescriptor {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+
+        Ok(Self {
+            specimen_type: component(&dst, 0),
+            specimen_source: component(&dst, 1),
+            components: trailing(&dst, Option::is_none),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        join(
+            &[
+                self.specimen_type.as_deref(),
+                self.specimen_source.as_deref(),
+            ],
+            self.components,
+            delimiters,
+        )
+    }
+}
+
+/* Report Type */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReportType {
+    Order,
+    Correction,
+    Preliminary,
+    Final,
+    Cancelled,
+    Pending,
+    NoOrder,
+    NoPatient,
+    QueryResponse,
+}
+
+impl FromStr for ReportType {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "O" => Ok(Self::Order),
+            "C" => Ok(Self::Correction),
+            "P" => Ok(Self::Preliminary),
+            "F" => Ok(Self::Final),
+            "X" => Ok(Self::Cancelled),
+            "I" => Ok(Self::Pending),
+            "Y" => Ok(Self::NoOrder),
+            "Z" => Ok(Self::NoPatient),
+            "Q" => Ok(Self::QueryResponse),
+            _ => Err(ASTMError::InvalidReportTypeValue),
+        }
+    }
+}
+
+impl std::fmt::Display for ReportType {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Order => fmt.write_str("O")?,
+            Self::Correction => fmt.write_str("C")?,
+            Self::Preliminary => fmt.write_str("P")?,
+            Self::Final => fmt.write_str("F")?,
+            Self::Cancelled => fmt.write_str("X")?,
+            Self::Pending => fmt.write_str("I")?,
+            Self::NoOrder => fmt.write_str("Y")?,
+            Self::NoPatient => fmt.write_str("Z")?,
+            Self::QueryResponse => fmt.write_str("Q")?,
+        }
+        Ok(())
+    }
+}
+
+/* Abnormal Flag */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AbnormalFlag {
+    BelowLowNormal,
+    AboveHighNormal,
+    BelowPanicLow,
+    AbovePanicHigh,
+    BelowAbsoluteLow,
+    AboveAbsoluteHigh,
+    Normal,
+    Abnormal,
+    SignificantChangeUp,
+    SignificantChangeDown,
+    Better,
+    Worse,
+    Other(String),
+}
+
+impl FromStr for AbnormalFlag {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "L" => Ok(Self::BelowLowNormal),
+            "H" => Ok(Self::AboveHighNormal),
+            "LL" => Ok(Self::BelowPanicLow),
+            "HH" => Ok(Self::AbovePanicHigh),
+            "<" => Ok(Self::BelowAbsoluteLow),
+            ">" => Ok(Self::AboveAbsoluteHigh),
+            "N" => Ok(Self::Normal),
+            "A" => Ok(Self::Abnormal),
+            "U" => Ok(Self::SignificantChangeUp),
+            "D" => Ok(Self::SignificantChangeDown),
+            "B" => Ok(Self::Better),
+            "W" => Ok(Self::Worse),
+            _ => Ok(Self::Other(src.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AbnormalFlag {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::BelowLowNormal => fmt.write_str("L")?,
+            Self::AboveHighNormal => fmt.write_str("H")?,
+            Self::BelowPanicLow => fmt.write_str("LL")?,
+            Self::AbovePanicHigh => fmt.write_str("HH")?,
+            Self::BelowAbsoluteLow => fmt.write_str("<")?,
+            Self::AboveAbsoluteHigh => fmt.write_str(">")?,
+            Self::Normal => fmt.write_str("N")?,
+            Self::Abnormal => fmt.write_str("A")?,
+            Self::SignificantChangeUp => fmt.write_str("U")?,
+            Self::SignificantChangeDown => fmt.write_str("D")?,
+            Self::Better => fmt.write_str("B")?,
+            Self::Worse => fmt.write_str("W")?,
+            Self::Other(t) => fmt.write_str(t)?,
+        }
+        Ok(())
+    }
+}
+
+/* Result Status */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResultStatus {
+    Correction,
+    Preliminary,
+    Final,
+    CannotBeDone,
+    Pending,
+    Partial,
+    MicLevel,
+    Retransmitted,
+    NewOrder,
+    QueryResponse,
+    Verified,
+    Warning,
+}
+
+impl FromStr for ResultStatus {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "C" => Ok(Self::Correction),
+            "P" => Ok(Self::Preliminary),
+            "F" => Ok(Self::Final),
+            "X" => Ok(Self::CannotBeDone),
+            "I" => Ok(Self::Pending),
+            "S" => Ok(Self::Partial),
+            "M" => Ok(Self::MicLevel),
+            "R" => Ok(Self::Retransmitted),
+            "N" => Ok(Self::NewOrder),
+            "Q" => Ok(Self::QueryResponse),
+            "V" => Ok(Self::Verified),
+            "W" => Ok(Self::Warning),
+            _ => Err(ASTMError::InvalidResultStatusValue),
+        }
+    }
+}
+
+impl std::fmt::Display for ResultStatus {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Correction => fmt.write_str("C")?,
+            Self::Preliminary => fmt.write_str("P")?,
+            Self::Final => fmt.write_str("F")?,
+            Self::CannotBeDone => fmt.write_str("X")?,
+            Self::Pending => fmt.write_str("I")?,
+            Self::Partial => fmt.write_str("S")?,
+            Self::MicLevel => fmt.write_str("M")?,
+            Self::Retransmitted => fmt.write_str("R")?,
+            Self::NewOrder => fmt.write_str("N")?,
+            Self::QueryResponse => fmt.write_str("Q")?,
+            Self::Verified => fmt.write_str("V")?,
+            Self::Warning => fmt.write_str("W")?,
+        }
+        Ok(())
+    }
+}
+
+/* Comment Source */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommentSource {
+    Practice,
+    InformationSystem,
+    Instrument,
+}
+
+impl FromStr for CommentSource {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "P" => Ok(Self::Practice),
+            "L" => Ok(Self::InformationSystem),
+            "I" => Ok(Self::Instrument),
+            _ => Err(ASTMError::InvalidCommentSourceValue),
+        }
+    }
+}
+
+impl std::fmt::Display for CommentSource {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Practice => fmt.write_str("P")?,
+            Self::InformationSystem => fmt.write_str("L")?,
+            Self::Instrument => fmt.write_str("I")?,
+        }
+        Ok(())
+    }
+}
+
+/* Comment Type */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommentType {
+    Generic,
+    TestName,
+    Positive,
+    Negative,
+    InstrumentFlag,
+}
+
+impl FromStr for CommentType {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "G" => Ok(Self::Generic),
+            "T" => Ok(Self::TestName),
+            "P" => Ok(Self::Positive),
+            "N" => Ok(Self::Negative),
+            "I" => Ok(Self::InstrumentFlag),
+            _ => Err(ASTMError::InvalidCommentTypeValue),
+        }
+    }
+}
+
+impl std::fmt::Display for CommentType {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Generic => fmt.write_str("G")?,
+            Self::TestName => fmt.write_str("T")?,
+            Self::Positive => fmt.write_str("P")?,
+            Self::Negative => fmt.write_str("N")?,
+            Self::InstrumentFlag => fmt.write_str("I")?,
+        }
+        Ok(())
+    }
+}
+
+/* Range ID */
+
+/// Starting or ending ID of a request, `ALL` or a patient ID and a
+/// specimen ID.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeId {
+    pub patient_id: Option<String>,
+    pub specimen_id: Option<String>,
+    /// Components after the specimen ID, defined by the manufacturer.
+    pub extra: Vec<Option<String>>,
+    /// Components parsed when the last ones are empty, 0 otherwise.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub components: usize,
+}
+
+impl Value for RangeId {
+    fn parse(src: &str, delimiters: &Delimiters) -> Result<Self> {
+        let dst = components(src, delimiters);
+
+        Ok(Self {
+            patient_id: component(&dst, 0),
+            specimen_id: component(&dst, 1),
+            components: trailing(&dst, Option::is_none),
+            extra: dst.into_iter().skip(2).collect(),
+        })
+    }
+
+    fn serialize(&self, delimiters: &Delimiters) -> String {
+        let mut dst = vec![self.patient_id.as_deref(), self.specimen_id.as_deref()];
+        dst.extend(self.extra.iter().map(|t| t.as_deref()));
+        join(&dst, self.components, delimiters)
+    }
+}
+
+/* Request Status */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RequestStatus {
+    Correction,
+    Preliminary,
+    Final,
+    CannotBeDone,
+    Pending,
+    Partial,
+    MicLevel,
+    Retransmitted,
+    Cancel,
+    NewOrEdited,
+    OrdersAndDemographics,
+    Demographics,
+}
+
+impl FromStr for RequestStatus {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "C" => Ok(Self::Correction),
+            "P" => Ok(Self::Preliminary),
+            "F" => Ok(Self::Final),
+            "X" => Ok(Self::CannotBeDone),
+            "I" => Ok(Self::Pending),
+            "S" => Ok(Self::Partial),
+            "M" => Ok(Self::MicLevel),
+            "R" => Ok(Self::Retransmitted),
+            "A" => Ok(Self::Cancel),
+            "N" => Ok(Self::NewOrEdited),
+            "O" => Ok(Self::OrdersAndDemographics),
+            "D" => Ok(Self::Demographics),
+            _ => Err(ASTMError::InvalidRequestStatusValue),
+        }
+    }
+}
+
+impl std::fmt::Display for RequestStatus {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Correction => fmt.write_str("C")?,
+            Self::Preliminary => fmt.write_str("P")?,
+            Self::Final => fmt.write_str("F")?,
+            Self::CannotBeDone => fmt.write_str("X")?,
+            Self::Pending => fmt.write_str("I")?,
+            Self::Partial => fmt.write_str("S")?,
+            Self::MicLevel => fmt.write_str("M")?,
+            Self::Retransmitted => fmt.write_str("R")?,
+            Self::Cancel => fmt.write_str("A")?,
+            Self::NewOrEdited => fmt.write_str("N")?,
+            Self::OrdersAndDemographics => fmt.write_str("O")?,
+            Self::Demographics => fmt.write_str("D")?,
+        }
+        Ok(())
+    }
+}
+
+/* Termination Code */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TerminationCode {
+    Normal,
+    SenderAborted,
+    ReceiverAborted,
+    SystemError,
+    QueryError,
+    NoInformation,
+    QueryProcessed,
+}
+
+impl FromStr for TerminationCode {
+    type Err = ASTMError;
+
+    fn from_str(src: &str) -> Result<Self> {
+        match src {
+            "N" => Ok(Self::Normal),
+            "T" => Ok(Self::SenderAborted),
+            "R" => Ok(Self::ReceiverAborted),
+            "E" => Ok(Self::SystemError),
+            "Q" => Ok(Self::QueryError),
+            "I" => Ok(Self::NoInformation),
+            "F" => Ok(Self::QueryProcessed),
+            _ => Err(ASTMError::InvalidTerminationCodeValue),
+        }
+    }
+}
+
+impl std::fmt::Display for TerminationCode {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Normal => fmt.write_str("N")?,
+            Self::SenderAborted => fmt.write_str("T")?,
+            Self::ReceiverAborted => fmt.write_str("R")?,
+            Self::SystemError => fmt.write_str("E")?,
+            Self::QueryError => fmt.write_str("Q")?,
+            Self::NoInformation => fmt.write_str("I")?,
+            Self::QueryProcessed => fmt.write_str("F")?,
+        }
+        Ok(())
+    }
+}
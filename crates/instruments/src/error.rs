@@ -16,6 +16,8 @@ pub enum InstError {
     ParseDriverYaml(String, String),
     #[error("Could not open driver.yaml file. {0}")]
     OpenDriverYaml(String),
+    #[error("Invalid encoding in driver {1}. {0}")]
+    InvalidEncoding(String, String),
 }
 
 impl From<InstError> for String {
@@ -1,3 +1,5 @@
+use astm::values::TestIdLayout;
+use astm::ASTM;
 use log::info;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -7,6 +9,8 @@ use tokio::io::AsyncReadExt;
 use tokio::sync::Mutex;
 
 mod error;
+#[cfg(test)]
+mod tests;
 
 const INST_PATH: &str = "drivers";
 const YAML_FILE: &str = "driver.yaml";
@@ -14,6 +18,7 @@ const YAML_FILE: &str = "driver.yaml";
 pub use error::InstError;
 pub type Result<T> = std::result::Result<T, InstError>;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 enum Protocol {
@@ -28,6 +33,24 @@ enum Mode {
     Client,
 }
 
+/// Framing of the ASTM messages.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Link {
+    #[default]
+    E1381,
+    Raw,
+}
+
+impl From<&Link> for astm::LinkMode {
+    fn from(src: &Link) -> Self {
+        match src {
+            Link::E1381 => astm::LinkMode::E1381,
+            Link::Raw => astm::LinkMode::Raw,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct Driver {
@@ -35,9 +58,108 @@ struct Driver {
     version: String,
     protocol: Protocol,
     modes: Vec<Mode>,
+    #[serde(default)]
+    link: Link,
+    encoding: Option<EncodingConfig>,
+    trace: Option<TraceConfig>,
+    #[serde(default)]
+    quirks: QuirksConfig,
+    /// Names of the manufacturer components of the universal test ID.
+    #[serde(default)]
+    test_id: Vec<String>,
+}
+
+/// Raw wire capture of the instrument connections.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceConfig {
+    /// Trace file, relative to the driver folder.
+    path: PathBuf,
+    #[serde(default = "default_trace_max_size")]
+    max_size: u64,
+    #[serde(default = "default_trace_max_files")]
+    max_files: usize,
+}
+
+fn default_trace_max_size() -> u64 {
+    astm::trace::DEFAULT_MAX_SIZE
+}
+
+fn default_trace_max_files() -> usize {
+    astm::trace::DEFAULT_MAX_FILES
+}
+
+/// Character encoding of the instrument, by name or WHATWG label.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodingConfig {
+    label: String,
+    /// Replace unmappable characters instead of rejecting the frame.
+    #[serde(default)]
+    replacement: bool,
+}
+
+impl TryFrom<&EncodingConfig> for astm::CharEncoding {
+    type Error = astm::ASTMError;
+
+    fn try_from(src: &EncodingConfig) -> std::result::Result<Self, Self::Error> {
+        let encoding = astm::CharEncoding::from_label(&src.label)?;
+
+        match src.replacement {
+            true => Ok(encoding.with_replacement()),
+            false => Ok(encoding),
+        }
+    }
+}
+
+/// Framing deviations tolerated for the instrument, all off by default.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct QuirksConfig {
+    lowercase_checksum: bool,
+    missing_lf: bool,
+    checksum_without_frame_number: bool,
+    stray_whitespace: bool,
+}
+
+impl From<&QuirksConfig> for astm::Quirks {
+    fn from(src: &QuirksConfig) -> Self {
+        astm::Quirks {
+            lowercase_checksum: src.lowercase_checksum,
+            missing_lf: src.missing_lf,
+            checksum_without_frame_number: src.checksum_without_frame_number,
+            stray_whitespace: src.stray_whitespace,
+        }
+    }
+}
+
+impl From<&TraceConfig> for astm::Trace {
+    fn from(src: &TraceConfig) -> Self {
+        astm::Trace::new(&src.path)
+            .max_size(src.max_size)
+            .max_files(src.max_files)
+    }
 }
 
 impl Driver {
+    /// Link builder of the instrument, with every setting of the driver.
+    fn astm<I: Clone>(&self, instrument: I) -> astm::Result<ASTM<I>> {
+        let mut dst = ASTM::new(instrument)
+            .link_mode(astm::LinkMode::from(&self.link))
+            .quirks(astm::Quirks::from(&self.quirks))
+            .test_id_layout(TestIdLayout::new(&self.test_id));
+
+        if let Some(encoding) = &self.encoding {
+            dst = dst.encoding(astm::CharEncoding::try_from(encoding)?);
+        }
+
+        if let Some(trace) = &self.trace {
+            dst = dst.trace(astm::Trace::from(trace));
+        }
+
+        Ok(dst)
+    }
+
     async fn read(path: PathBuf, folder_name: &str) -> Result<Self> {
         match fs::File::open(path).await {
             Ok(mut file) => {
@@ -97,12 +219,45 @@ impl Instruments {
                 return Err(InstError::MissingDriverYaml(folder_name));
             }
 
-            let driver = Driver::read(yaml_path, &folder_name).await?;
+            let mut driver = Driver::read(yaml_path, &folder_name).await?;
             info!(
                 "Found driver {} ({}) in folder {}.",
                 driver.name, driver.version, folder_name
             );
 
+            if let Some(trace) = driver.trace.as_mut() {
+                trace.path = driver_path.join(&trace.path);
+                info!(
+                    "Wire capture of driver {} in {:?}.",
+                    driver.name, trace.path
+                );
+            }
+
+            let astm = driver
+                .astm(())
+                .map_err(|t| InstError::InvalidEncoding(t.to_string(), folder_name.clone()))?;
+            let settings = astm.settings();
+
+            if driver.encoding.is_some() {
+                info!("Driver {} uses {:?}.", driver.name, settings.encoding());
+            }
+
+            if settings.link_mode() == astm::LinkMode::Raw {
+                info!("Driver {} sends raw records, without framing.", driver.name);
+            }
+
+            if *settings.quirks() != astm::Quirks::default() {
+                info!("Driver {} tolerates {:?}.", driver.name, settings.quirks());
+            }
+
+            if !driver.test_id.is_empty() {
+                info!(
+                    "Driver {} names test ID components {:?}.",
+                    driver.name,
+                    settings.test_id_layout().names()
+                );
+            }
+
             // check duplicated
 
             new.push(driver);
@@ -113,4 +268,17 @@ impl Instruments {
 
         Ok(())
     }
+
+    /// Link builder of an instrument, set up from the `driver.yaml` of
+    /// the driver with the given name. The app does not start instrument
+    /// links yet, so these settings only reach the links run from here.
+    pub async fn astm<I: Clone>(&self, name: &str, instrument: I) -> Option<ASTM<I>> {
+        let drivers = self.drivers.lock().await;
+
+        // The settings were checked by the scan, so the builder is made.
+        drivers
+            .iter()
+            .find(|t| t.name == name)
+            .and_then(|t| t.astm(instrument).ok())
+    }
 }
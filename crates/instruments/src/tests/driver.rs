@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use crate::Driver;
+
+const DRIVER_YAML: &str = "
+name: Alinity
+version: 1.0.0
+protocol: astm
+modes: [server]
+link: raw
+encoding:
+  label: iso-8859-1
+  replacement: true
+trace:
+  path: traces/alinity.log
+  maxFiles: 3
+quirks:
+  lowercaseChecksum: true
+  missingLf: true
+testId: [assay_number, assay_name, dilution]
+";
+
+#[test]
+fn driver_settings() {
+    let driver: Driver = serde_yaml::from_str(DRIVER_YAML).unwrap();
+    let astm = driver.astm("Alinity".to_string()).unwrap();
+    let settings = astm.settings();
+
+    assert_eq!(settings.link_mode(), astm::LinkMode::Raw);
+    assert_eq!(
+        *settings.encoding(),
+        astm::CharEncoding::ISO_8859_1.with_replacement()
+    );
+    assert_eq!(
+        *settings.quirks(),
+        astm::Quirks {
+            lowercase_checksum: true,
+            missing_lf: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        settings.test_id_layout().names(),
+        ["assay_number", "assay_name", "dilution"]
+    );
+    assert_eq!(astm.trace_path(), Some(Path::new("traces/alinity.log")));
+}
+
+#[test]
+fn driver_defaults() {
+    let driver: Driver =
+        serde_yaml::from_str("{name: Cobas, version: '1', protocol: astm, modes: [client]}")
+            .unwrap();
+    let astm = driver.astm(()).unwrap();
+    let settings = astm.settings();
+
+    assert_eq!(settings.link_mode(), astm::LinkMode::E1381);
+    assert_eq!(*settings.encoding(), astm::CharEncoding::default());
+    assert_eq!(*settings.quirks(), astm::Quirks::default());
+    assert!(settings.test_id_layout().names().is_empty());
+    assert_eq!(astm.trace_path(), None);
+}
+
+#[test]
+fn driver_with_unknown_encoding() {
+    let driver: Driver = serde_yaml::from_str(
+        "{name: Cobas, version: '1', protocol: astm, modes: [client], encoding: {label: ebcdic}}",
+    )
+    .unwrap();
+    assert!(driver.astm(()).is_err());
+}
@@ -0,0 +1 @@
+mod driver;
\ No newline at end of file